@@ -57,18 +57,15 @@ fn main() {
     let map_acct = load_mapping( &map_data ).unwrap();
 
     // iget and print each Product in Mapping directory
-    let mut i = 0;
-    for prod_akey in &map_acct.products {
+    for prod_akey in map_acct.iter_products() {
       let prod_pkey = Pubkey::new( &prod_akey.val );
       let prod_data = clnt.get_account_data( &prod_pkey ).unwrap();
       let prod_acct = load_product( &prod_data ).unwrap();
 
       // print key and reference data for this Product
       println!( "product_account .. {:?}", prod_pkey );
-      for (key, val) in prod_acct.iter() {
-        if key.len() > 0 {
-          println!( "  {:.<16} {}", key, val );
-        }
+      for (key, val) in prod_acct.iter_attributes() {
+        println!( "  {:.<16} {}", key, val );
       }
 
       // print all Prices that correspond to this Product
@@ -114,18 +111,12 @@ fn main() {
           }
 
           // go to next price account in list
-          if pa.next.is_valid() {
-            px_pkey = Pubkey::new( &pa.next.val );
-          } else {
-            break;
+          match pa.next_price_key() {
+            Some(next_pkey) => px_pkey = next_pkey,
+            None => break,
           }
         }
       }
-      // go to next product
-      i += 1;
-      if i == map_acct.num {
-        break;
-      }
     }
 
     // go to next Mapping account in list
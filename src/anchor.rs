@@ -0,0 +1,64 @@
+//! Interop with the [Anchor](https://www.anchor-lang.com/) framework, enabled by the `anchor`
+//! feature.
+
+use core::ops::Deref;
+
+use anchor_lang::{
+  prelude::{ProgramError, Pubkey},
+  solana_program::pubkey,
+  AccountDeserialize, AccountSerialize, Owner, Result,
+};
+
+use crate::{load_price, Price};
+
+/// The key of the Pyth oracle program on Solana mainnet-beta, which owns its price accounts.
+pub const MAINNET_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+/**
+ * A Pyth `Price` account that can be declared as an `Account` in an Anchor program:
+ *
+ * ```ignore
+ * use anchor_lang::prelude::*;
+ * use pyth_client::PriceAccount;
+ *
+ * #[derive(Accounts)]
+ * pub struct ReadPrice<'info> {
+ *   pub price: Box<Account<'info, PriceAccount>>,
+ * }
+ * ```
+ *
+ * The account data is validated as in `load_price`, and Anchor checks that the account is owned by
+ * `MAINNET_PROGRAM_ID`. On other clusters, take the account as an `UncheckedAccount` and use
+ * `load_price_account_info` with the program key of that cluster instead. Since Pyth accounts have
+ * no Anchor discriminator, they cannot be used with `AccountLoader`. Boxing the account avoids
+ * copying the large `Price` onto the stack.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceAccount(pub Price);
+
+impl Deref for PriceAccount {
+  type Target = Price;
+
+  fn deref(&self) -> &Price {
+    &self.0
+  }
+}
+
+impl AccountDeserialize for PriceAccount {
+  fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+    let price = load_price(buf).map_err(ProgramError::from)?;
+    // `load_price` returns a reference into the data on little-endian targets.
+    #[cfg(target_endian = "little")]
+    let price = *price;
+    Ok(PriceAccount(price))
+  }
+}
+
+/** Price accounts are only written by the oracle program, so serializing does nothing. */
+impl AccountSerialize for PriceAccount {}
+
+impl Owner for PriceAccount {
+  fn owner() -> Pubkey {
+    MAINNET_PROGRAM_ID
+  }
+}
@@ -0,0 +1,273 @@
+//! Decoding of Pyth accounts from their little-endian wire format.
+//!
+//! On little-endian targets accounts are read zero-copy by casting the account data, so this
+//! module is only used by the loaders on big-endian targets. It is compiled everywhere so that
+//! both paths can be checked against each other.
+
+#![cfg_attr(target_endian = "little", allow(dead_code))]
+
+use core::convert::TryFrom;
+
+use crate::{
+  AccKey, AccKeyU64, AccountType, CorpAction, Ema, Mapping, Price, PriceComp, PriceInfo, PriceStatus,
+  PriceType, Product, MAP_TABLE_SIZE, PROD_ATTR_SIZE,
+};
+
+/// Types that can be decoded field-by-field from little-endian account data.
+pub(crate) trait FromLeBytes: Sized {
+  /// Decode a value from the front of `reader`, or return `None` if the data is invalid.
+  fn read(reader: &mut LeReader) -> Option<Self>;
+
+  /// Decode a value from the start of `data`, which must hold at least `size_of::<Self>()` bytes.
+  fn from_le_bytes(data: &[u8]) -> Option<Self> {
+    Self::read(&mut LeReader { data })
+  }
+}
+
+/// A cursor over little-endian bytes.
+pub(crate) struct LeReader<'a> {
+  data: &'a [u8],
+}
+
+impl<'a> LeReader<'a> {
+  fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+    if self.data.len() < N {
+      return None;
+    }
+    let (head, tail) = self.data.split_at(N);
+    self.data = tail;
+    let mut buf = [0u8; N];
+    buf.copy_from_slice(head);
+    Some(buf)
+  }
+
+  fn u32(&mut self) -> Option<u32> {
+    self.take().map(u32::from_le_bytes)
+  }
+
+  fn i32(&mut self) -> Option<i32> {
+    self.take().map(i32::from_le_bytes)
+  }
+
+  fn u64(&mut self) -> Option<u64> {
+    self.take().map(u64::from_le_bytes)
+  }
+
+  fn i64(&mut self) -> Option<i64> {
+    self.take().map(i64::from_le_bytes)
+  }
+}
+
+impl FromLeBytes for AccountType {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    AccountType::try_from(reader.u32()?).ok()
+  }
+}
+
+impl FromLeBytes for PriceStatus {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    PriceStatus::try_from(reader.u32()?).ok()
+  }
+}
+
+impl FromLeBytes for CorpAction {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    CorpAction::try_from(reader.u32()?).ok()
+  }
+}
+
+impl FromLeBytes for PriceType {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    PriceType::try_from(reader.u32()?).ok()
+  }
+}
+
+impl FromLeBytes for AccKey {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    Some(AccKey { val: reader.take()? })
+  }
+}
+
+impl FromLeBytes for AccKeyU64 {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    Some(AccKeyU64 { val: [reader.u64()?, reader.u64()?, reader.u64()?, reader.u64()?] })
+  }
+}
+
+impl FromLeBytes for Ema {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    Some(Ema { val: reader.i64()?, numer: reader.i64()?, denom: reader.i64()? })
+  }
+}
+
+impl FromLeBytes for PriceInfo {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    Some(PriceInfo {
+      price: reader.i64()?,
+      conf: reader.u64()?,
+      status: PriceStatus::read(reader)?,
+      corp_act: CorpAction::read(reader)?,
+      pub_slot: reader.u64()?,
+    })
+  }
+}
+
+impl FromLeBytes for PriceComp {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    Some(PriceComp {
+      publisher: AccKey::read(reader)?,
+      agg: PriceInfo::read(reader)?,
+      latest: PriceInfo::read(reader)?,
+    })
+  }
+}
+
+impl FromLeBytes for Mapping {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    let magic = reader.u32()?;
+    let ver = reader.u32()?;
+    let atype = reader.u32()?;
+    let size = reader.u32()?;
+    let num = reader.u32()?;
+    let unused = reader.u32()?;
+    let next = AccKey::read(reader)?;
+    let mut products = [AccKey::default(); MAP_TABLE_SIZE];
+    for product in products.iter_mut() {
+      *product = AccKey::read(reader)?;
+    }
+    Some(Mapping { magic, ver, atype, size, num, unused, next, products })
+  }
+}
+
+impl FromLeBytes for Product {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    Some(Product {
+      magic: reader.u32()?,
+      ver: reader.u32()?,
+      atype: reader.u32()?,
+      size: reader.u32()?,
+      px_acc: AccKey::read(reader)?,
+      attr: reader.take::<PROD_ATTR_SIZE>()?,
+    })
+  }
+}
+
+impl FromLeBytes for Price {
+  fn read(reader: &mut LeReader) -> Option<Self> {
+    let mut price = Price {
+      magic: reader.u32()?,
+      ver: reader.u32()?,
+      atype: reader.u32()?,
+      size: reader.u32()?,
+      ptype: PriceType::read(reader)?,
+      expo: reader.i32()?,
+      num: reader.u32()?,
+      num_qt: reader.u32()?,
+      last_slot: reader.u64()?,
+      valid_slot: reader.u64()?,
+      ema_price: Ema::read(reader)?,
+      ema_confidence: Ema::read(reader)?,
+      drv1: reader.i64()?,
+      drv2: reader.i64()?,
+      prod: AccKey::read(reader)?,
+      next: AccKey::read(reader)?,
+      prev_slot: reader.u64()?,
+      prev_price: reader.i64()?,
+      prev_conf: reader.u64()?,
+      drv3: reader.i64()?,
+      agg: PriceInfo::read(reader)?,
+      ..Default::default()
+    };
+    for comp in price.comp.iter_mut() {
+      *comp = PriceComp::read(reader)?;
+    }
+    Some(price)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::mem::size_of;
+  use crate::endian::FromLeBytes;
+  use crate::{AccKey, Mapping, Price, PriceStatus, Product, MAP_TABLE_SIZE};
+
+  fn put(buf: &mut [u8], offset: usize, val: &[u8]) {
+    buf[offset..offset + val.len()].copy_from_slice(val);
+  }
+
+  /// Price account bytes built by hand at the documented field offsets.
+  fn price_bytes() -> Vec<u8> {
+    let mut data = vec![0u8; size_of::<Price>()];
+    put(&mut data, 0, &crate::MAGIC.to_le_bytes());
+    put(&mut data, 4, &crate::VERSION_2.to_le_bytes());
+    put(&mut data, 8, &3u32.to_le_bytes());
+    put(&mut data, 12, &3312u32.to_le_bytes());
+    put(&mut data, 16, &1u32.to_le_bytes());
+    put(&mut data, 20, &(-8i32).to_le_bytes());
+    put(&mut data, 24, &2u32.to_le_bytes());
+    put(&mut data, 32, &0x0102030405060708u64.to_le_bytes());
+    put(&mut data, 48, &(-12345i64).to_le_bytes());
+    put(&mut data, 144, &[9; 32]);
+    // agg
+    put(&mut data, 208, &(-5000i64).to_le_bytes());
+    put(&mut data, 216, &17u64.to_le_bytes());
+    put(&mut data, 224, &1u32.to_le_bytes());
+    put(&mut data, 232, &100u64.to_le_bytes());
+    // comp[31].latest.price
+    put(&mut data, 240 + 31 * 96 + 64, &42i64.to_le_bytes());
+    data
+  }
+
+  #[test]
+  fn test_price_from_le_bytes() {
+    let mut data = price_bytes();
+    let price = Price::from_le_bytes(&data).unwrap();
+    assert_eq!(price.magic, crate::MAGIC);
+    assert_eq!(price.atype, 3);
+    assert_eq!(price.size, 3312);
+    assert_eq!(price.expo, -8);
+    assert_eq!(price.num, 2);
+    assert_eq!(price.last_slot, 0x0102030405060708);
+    assert_eq!(price.ema_price.val, -12345);
+    assert_eq!(price.next, AccKey { val: [9; 32] });
+    assert_eq!(price.agg.price, -5000);
+    assert_eq!(price.agg.conf, 17);
+    assert_eq!(price.agg.status, PriceStatus::Trading);
+    assert_eq!(price.agg.pub_slot, 100);
+    assert_eq!(price.comp[31].latest.price, 42);
+    assert_eq!(crate::load_price(&data).unwrap().agg.price, -5000);
+
+    assert_eq!(Price::from_le_bytes(&data[..size_of::<Price>() - 1]), None);
+    // Unknown enum discriminants are rejected.
+    put(&mut data, 224, &7u32.to_le_bytes());
+    assert_eq!(Price::from_le_bytes(&data), None);
+  }
+
+  #[test]
+  fn test_mapping_and_product_from_le_bytes() {
+    let mut data = vec![0u8; size_of::<Mapping>()];
+    put(&mut data, 16, &2u32.to_le_bytes());
+    put(&mut data, 24 + 32 + (MAP_TABLE_SIZE - 1) * 32, &[5; 32]);
+    let mapping = Mapping::from_le_bytes(&data).unwrap();
+    assert_eq!(mapping.num, 2);
+    assert_eq!(mapping.products[MAP_TABLE_SIZE - 1], AccKey { val: [5; 32] });
+
+    let mut data = vec![0u8; size_of::<Product>()];
+    put(&mut data, 12, &512u32.to_le_bytes());
+    put(&mut data, 48, &[3, b'f', b'o', b'o']);
+    let product = Product::from_le_bytes(&data).unwrap();
+    assert_eq!(product.size, 512);
+    assert_eq!(product.get_attribute("foo"), Some(""));
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_matches_zero_copy_layout() {
+    use bytemuck::bytes_of;
+
+    let data = price_bytes();
+    let price = Price::from_le_bytes(&data).unwrap();
+    assert_eq!(&price, crate::load_price(&data).unwrap());
+    assert_eq!(bytes_of(&price), &data[..]);
+  }
+}
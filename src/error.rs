@@ -1,25 +1,233 @@
-use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use solana_program::program_error::ProgramError;
 use thiserror::Error;
 
 /// Errors that may be returned by Pyth.
-#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
 pub enum PythError {
   // 0
-  /// Invalid account data -- either insufficient data, or incorrect magic number
-  #[error("Failed to convert account into a Pyth account")]
+  /// Invalid account data, e.g., an incorrect magic number or an inconsistent header
+  #[error("invalid account data")]
   InvalidAccountData,
-  /// Wrong version number
-  #[error("Incorrect version number for Pyth account")]
+  /// Wrong version number. The loaders report `UnsupportedVersion` instead.
+  #[error("bad version number: expected {}", crate::VERSION)]
   BadVersionNumber,
   /// Tried reading an account with the wrong type, e.g., tried to read
   /// a price account as a product account.
-  #[error("Incorrect account type")]
+  #[error("wrong account type")]
   WrongAccountType,
+  /// The account data is shorter than the struct it is being read as.
+  #[error("account too small: expected at least {expected} bytes, got {actual}")]
+  AccountTooSmall { expected: usize, actual: usize },
+  /// The price is not currently trading, so no price is available.
+  #[error("price is not trading")]
+  PriceNotTrading,
+  /// The base price of a derived price is not currently trading.
+  #[error("base price is not trading")]
+  BasePriceNotTrading,
+  /// The quote price of a derived price is not currently trading.
+  #[error("quote price is not trading")]
+  QuotePriceNotTrading,
+  /// The result of a price computation is not representable, e.g., because of a division by
+  /// zero or an overflow.
+  #[error("price arithmetic failed")]
+  ArithmeticFailed,
+  /// The account is not owned by the expected program.
+  #[error("account is not owned by the expected program")]
+  WrongOwner,
+  /// The account has a version that this crate cannot read. Versions above `max_supported`
+  /// may require upgrading this crate.
+  #[error("unsupported account version {found}: supported versions are up to {max_supported}")]
+  UnsupportedVersion { found: u32, max_supported: u32 },
+  /// The exponent of a computed price does not fit in an i32.
+  #[error("exponent overflow")]
+  ExponentOverflow,
+  /// A string could not be parsed as a `PriceStatus`.
+  #[error("invalid price status")]
+  InvalidPriceStatus,
+  /// Account data could not be decoded from base64.
+  #[error("account data is not valid base64")]
+  InvalidBase64,
+  /// The price was published too many slots before the current slot.
+  #[error("price is stale")]
+  StalePrice,
+  /// The confidence interval of the price is too wide relative to the price.
+  #[error("price confidence interval is too wide")]
+  ConfidenceTooWide,
+  /// The account data is not aligned as required by the struct it is being read as, so it
+  /// cannot be read in place. Account data provided by the Solana runtime is always aligned.
+  #[error("account data is misaligned")]
+  MisalignedAccountData,
+  /// The price account belongs to a different product than the expected one.
+  #[error("price account does not belong to the expected product")]
+  ProductMismatch,
+}
+
+/// An operation performed on each entry of a price basket, see `BasketError`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BasketOperation {
+  /// Multiplying the price by the quantity of the entry.
+  Mul,
+  /// Scaling the value of the entry to the exponent of the result.
+  Scale,
+  /// Adding the value of the entry to the sum of the previous entries.
+  Add,
+}
+
+impl core::fmt::Display for BasketOperation {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.write_str(match self {
+      BasketOperation::Mul => "mul",
+      BasketOperation::Scale => "scale",
+      BasketOperation::Add => "add",
+    })
+  }
+}
+
+/// Errors that may be returned by `Price::price_basket_checked`.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum BasketError {
+  /// The basket has no entries.
+  #[error("price basket is empty")]
+  EmptyBasket,
+  /// The price of the entry at `index` is not currently trading.
+  #[error("price of basket entry {index} is not trading")]
+  PriceNotTrading { index: usize },
+  /// The result of `operation` on the entry at `index` is not representable.
+  #[error("{operation} failed at basket entry {index}")]
+  ArithmeticFailed { index: usize, operation: BasketOperation },
+}
+
+impl PythError {
+  /// The error code reported in `ProgramError::Custom`. These codes are stable.
+  pub fn code(&self) -> u32 {
+    match self {
+      PythError::InvalidAccountData => 0,
+      PythError::BadVersionNumber => 1,
+      PythError::WrongAccountType => 2,
+      PythError::AccountTooSmall { .. } => 3,
+      PythError::PriceNotTrading => 4,
+      PythError::BasePriceNotTrading => 5,
+      PythError::QuotePriceNotTrading => 6,
+      PythError::ArithmeticFailed => 7,
+      PythError::WrongOwner => 8,
+      PythError::UnsupportedVersion { .. } => 9,
+      PythError::ExponentOverflow => 10,
+      PythError::InvalidPriceStatus => 11,
+      PythError::InvalidBase64 => 12,
+      PythError::StalePrice => 13,
+      PythError::ConfidenceTooWide => 14,
+      PythError::MisalignedAccountData => 15,
+      PythError::ProductMismatch => 16,
+    }
+  }
+}
+
+/// Maps an error code from `PythError::code` back to the error. The codes of errors that carry
+/// data, `AccountTooSmall` and `UnsupportedVersion`, give `None`, as the data cannot be recovered.
+impl FromPrimitive for PythError {
+  fn from_i64(n: i64) -> Option<Self> {
+    if n < 0 {
+      return None;
+    }
+    PythError::from_u64(n as u64)
+  }
+
+  fn from_u64(n: u64) -> Option<Self> {
+    Some(match n {
+      0 => PythError::InvalidAccountData,
+      1 => PythError::BadVersionNumber,
+      2 => PythError::WrongAccountType,
+      4 => PythError::PriceNotTrading,
+      5 => PythError::BasePriceNotTrading,
+      6 => PythError::QuotePriceNotTrading,
+      7 => PythError::ArithmeticFailed,
+      8 => PythError::WrongOwner,
+      10 => PythError::ExponentOverflow,
+      11 => PythError::InvalidPriceStatus,
+      12 => PythError::InvalidBase64,
+      13 => PythError::StalePrice,
+      14 => PythError::ConfidenceTooWide,
+      15 => PythError::MisalignedAccountData,
+      16 => PythError::ProductMismatch,
+      _ => return None,
+    })
+  }
 }
 
 impl From<PythError> for ProgramError {
   fn from(e: PythError) -> Self {
-    ProgramError::Custom(e as u32)
+    ProgramError::Custom(e.code())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use solana_program::program_error::ProgramError;
+  use crate::{BasketError, BasketOperation, PythError};
+
+  #[test]
+  fn test_error_codes() {
+    let errors = [
+      PythError::InvalidAccountData,
+      PythError::BadVersionNumber,
+      PythError::WrongAccountType,
+      PythError::AccountTooSmall { expected: 3312, actual: 3311 },
+      PythError::PriceNotTrading,
+      PythError::BasePriceNotTrading,
+      PythError::QuotePriceNotTrading,
+      PythError::ArithmeticFailed,
+      PythError::WrongOwner,
+      PythError::UnsupportedVersion { found: 3, max_supported: 2 },
+      PythError::ExponentOverflow,
+      PythError::InvalidPriceStatus,
+      PythError::InvalidBase64,
+      PythError::StalePrice,
+      PythError::ConfidenceTooWide,
+      PythError::MisalignedAccountData,
+      PythError::ProductMismatch,
+    ];
+    for (i, e) in errors.iter().enumerate() {
+      assert_eq!(ProgramError::from(e.clone()), ProgramError::Custom(i as u32));
+    }
+  }
+
+  #[test]
+  fn test_from_primitive() {
+    use num_traits::FromPrimitive;
+
+    assert_eq!(PythError::from_u32(0), Some(PythError::InvalidAccountData));
+    assert_eq!(PythError::from_u32(2), Some(PythError::WrongAccountType));
+    assert_eq!(PythError::from_i64(16), Some(PythError::ProductMismatch));
+    // Errors that carry data cannot be recovered from their codes.
+    assert_eq!(PythError::from_u32(3), None);
+    assert_eq!(PythError::from_u32(9), None);
+    assert_eq!(PythError::from_u32(17), None);
+    assert_eq!(PythError::from_i64(-1), None);
+  }
+
+  #[test]
+  fn test_basket_error_messages() {
+    assert_eq!(BasketError::EmptyBasket.to_string(), "price basket is empty");
+    assert_eq!(BasketError::PriceNotTrading { index: 2 }.to_string(), "price of basket entry 2 is not trading");
+    assert_eq!(
+      BasketError::ArithmeticFailed { index: 1, operation: BasketOperation::Scale }.to_string(),
+      "scale failed at basket entry 1"
+    );
+  }
+
+  #[test]
+  fn test_error_messages() {
+    assert_eq!(PythError::InvalidAccountData.to_string(), "invalid account data");
+    assert_eq!(PythError::BadVersionNumber.to_string(), "bad version number: expected 2");
+    assert_eq!(PythError::WrongAccountType.to_string(), "wrong account type");
+    assert_eq!(
+      PythError::AccountTooSmall { expected: 3312, actual: 3311 }.to_string(),
+      "account too small: expected at least 3312 bytes, got 3311"
+    );
+    assert_eq!(
+      PythError::UnsupportedVersion { found: 3, max_supported: 2 }.to_string(),
+      "unsupported account version 3: supported versions are up to 2"
+    );
   }
 }
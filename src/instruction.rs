@@ -1,10 +1,15 @@
 //! Program instructions for end-to-end testing and instruction counts
 
-use bytemuck::bytes_of;
+#[cfg(target_endian = "little")]
+use {
+  bytemuck::bytes_of,
+  crate::Price,
+};
 
-use crate::{PriceStatus, Price};
+use crate::PriceStatus;
 
 use {
+  alloc::{vec, vec::Vec},
   crate::id,
   borsh::{BorshDeserialize, BorshSerialize},
   solana_program::instruction::Instruction,
@@ -12,7 +17,8 @@ use {
 };
 
 /// Instructions supported by the pyth-client program, used for testing and
-/// instruction counts
+/// instruction counts. New variants are added at the end, so that the Borsh discriminants of the
+/// existing ones do not change.
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub enum PythClientInstruction {
   Divide {
@@ -41,10 +47,46 @@ pub enum PythClientInstruction {
 
   PriceStatusCheck {
     // A Price serialized as a vector of bytes. This field is stored as a vector of bytes (instead of a Price)
-    // so that we do not have to add Borsh serialization to all structs, which is expensive.
+    // so that we do not have to add Borsh serialization to all structs, which is expensive
+    // (it is only available with the `price-borsh` feature).
     price_account_data: Vec<u8>,  
+    current_slot: u64,
     expected_price_status: PriceStatus
-  }
+  },
+
+  Subtract {
+    x: PriceConf,
+    y: PriceConf,
+  },
+
+  Cmul {
+    x: PriceConf,
+    c: i64,
+    e: i32,
+  },
+
+  WeightedMean {
+    prices: Vec<(PriceConf, u64)>,
+  },
+
+  PriceNoOlderThan {
+    // A Price serialized as a vector of bytes, as in PriceStatusCheck.
+    price_account_data: Vec<u8>,
+    current_slot: u64,
+    max_slot_age: u64,
+  },
+
+  BatchStatusCheck {
+    // Prices serialized as vectors of bytes, as in PriceStatusCheck.
+    accounts: Vec<Vec<u8>>,
+    current_slot: u64,
+    expected: PriceStatus,
+  },
+
+  ToExponentNormalized {
+    x: PriceConf,
+    expo: i32,
+  },
 }
 
 pub fn divide(numerator: PriceConf, denominator: PriceConf) -> Instruction {
@@ -77,6 +119,36 @@ pub fn add(x: PriceConf, y: PriceConf) -> Instruction {
   }
 }
 
+pub fn subtract(x: PriceConf, y: PriceConf) -> Instruction {
+  Instruction {
+    program_id: id(),
+    accounts: vec![],
+    data: PythClientInstruction::Subtract { x, y }
+      .try_to_vec()
+      .unwrap(),
+  }
+}
+
+pub fn cmul(x: PriceConf, c: i64, e: i32) -> Instruction {
+  Instruction {
+    program_id: id(),
+    accounts: vec![],
+    data: PythClientInstruction::Cmul { x, c, e }
+      .try_to_vec()
+      .unwrap(),
+  }
+}
+
+pub fn weighted_mean(prices: Vec<(PriceConf, u64)>) -> Instruction {
+  Instruction {
+    program_id: id(),
+    accounts: vec![],
+    data: PythClientInstruction::WeightedMean { prices }
+      .try_to_vec()
+      .unwrap(),
+  }
+}
+
 pub fn scale_to_exponent(x: PriceConf, expo: i32) -> Instruction {
   Instruction {
     program_id: id(),
@@ -97,6 +169,16 @@ pub fn normalize(x: PriceConf) -> Instruction {
   }
 }
 
+pub fn to_exponent_normalized(x: PriceConf, expo: i32) -> Instruction {
+  Instruction {
+    program_id: id(),
+    accounts: vec![],
+    data: PythClientInstruction::ToExponentNormalized { x, expo }
+      .try_to_vec()
+      .unwrap(),
+  }
+}
+
 /// Noop instruction for comparison purposes
 pub fn noop() -> Instruction {
   Instruction {
@@ -106,12 +188,55 @@ pub fn noop() -> Instruction {
   }
 }
 
-// Returns ok if price account status matches given expected price status.
-pub fn price_status_check(price: &Price, expected_price_status: PriceStatus) -> Instruction {
+// Returns ok if price account status at `current_slot` matches given expected price status.
+// Only available on little-endian targets, where the in-memory layout of `Price` matches the account data.
+#[cfg(target_endian = "little")]
+pub fn price_status_check(price: &Price, current_slot: u64, expected_price_status: PriceStatus) -> Instruction {
   Instruction {
     program_id: id(), 
     accounts: vec![],
-    data: PythClientInstruction::PriceStatusCheck { price_account_data: bytes_of(price).to_vec(), expected_price_status }
+    data: PythClientInstruction::PriceStatusCheck {
+      price_account_data: bytes_of(price).to_vec(),
+      current_slot,
+      expected_price_status,
+    }
+      .try_to_vec()
+      .unwrap(),
+  }
+}
+
+// Gets the price of the given price account if it was published at most `max_slot_age` slots
+// before `current_slot`.
+// Only available on little-endian targets, where the in-memory layout of `Price` matches the account data.
+#[cfg(target_endian = "little")]
+pub fn price_no_older_than(price: &Price, current_slot: u64, max_slot_age: u64) -> Instruction {
+  Instruction {
+    program_id: id(),
+    accounts: vec![],
+    data: PythClientInstruction::PriceNoOlderThan {
+      price_account_data: bytes_of(price).to_vec(),
+      current_slot,
+      max_slot_age,
+    }
+      .try_to_vec()
+      .unwrap(),
+  }
+}
+
+// Returns ok if the status of every price account at `current_slot` matches the expected price status. Otherwise fails
+// with `ProgramError::Custom(i)`, where `i` is the index of the first account that fails to load or
+// has another status.
+// Only available on little-endian targets, where the in-memory layout of `Price` matches the account data.
+#[cfg(target_endian = "little")]
+pub fn batch_status_check(prices: &[Price], current_slot: u64, expected: PriceStatus) -> Instruction {
+  Instruction {
+    program_id: id(),
+    accounts: vec![],
+    data: PythClientInstruction::BatchStatusCheck {
+      accounts: prices.iter().map(|price| bytes_of(price).to_vec()).collect(),
+      current_slot,
+      expected,
+    }
       .try_to_vec()
       .unwrap(),
   }
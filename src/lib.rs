@@ -1,24 +1,45 @@
 //! A Rust library for consuming price feeds from the [pyth.network](https://pyth.network/) oracle on the Solana network.
 //!
 //! Please see the [crates.io page](https://crates.io/crates/pyth-client/) for documentation and example usage.
+//!
+//! The `std` feature is enabled by default. Without it the library is `#![no_std]`, and only the
+//! floating point conversions of `PriceConf` are unavailable.
 #![deprecated = "This crate has been deprecated. Please use pyth-sdk-solana instead."]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
-pub use self::price_conf::PriceConf;
-pub use self::error::PythError;
+pub use self::price_conf::{
+  PriceConf, Rounding, MAX_PD_V_I64, MAX_PD_V_U64, MIN_PD_V_I64, PD_EXPO, PD_SCALE,
+};
+pub use self::error::{BasketError, BasketOperation, PythError};
+#[cfg(feature = "anchor")]
+pub use self::anchor::{PriceAccount, MAINNET_PROGRAM_ID};
 
+#[cfg(feature = "anchor")]
+mod anchor;
+mod endian;
 mod entrypoint;
 mod error;
 mod price_conf;
 
 pub mod processor;
 pub mod instruction;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
-use std::mem::size_of;
+use core::convert::TryFrom;
+use core::mem::size_of;
+use alloc::string::String;
+use alloc::vec::Vec;
 use borsh::{BorshSerialize, BorshDeserialize};
-use bytemuck::{
-  cast_slice, from_bytes, try_cast_slice,
-  Pod, PodCastError, Zeroable,
-};
+use bytemuck::Zeroable;
+#[cfg(target_endian = "little")]
+use bytemuck::{from_bytes, try_from_bytes, Pod, PodCastError};
+
+use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+#[cfg(target_endian = "little")]
+use core::cell::Ref;
 
 #[cfg(target_arch = "bpf")]
 use solana_program::{clock::Clock, sysvar::Sysvar};
@@ -34,8 +55,28 @@ pub const PROD_HDR_SIZE       : usize = 48;
 pub const PROD_ATTR_SIZE      : usize = PROD_ACCT_SIZE - PROD_HDR_SIZE;
 pub const MAX_SLOT_DIFFERENCE : u64   = 25; 
 
+// Sizes of the headers preceding the product table of a mapping account and the components of a
+// price account. The used `size` of these accounts is never smaller.
+const MAP_HDR_SIZE            : usize = 56;
+const PRICE_HDR_SIZE          : usize = 240;
+
+/// Size in bytes of a mapping account, i.e., the header followed by `MAP_TABLE_SIZE` product keys.
+pub const MAP_ACCT_SIZE       : usize = MAP_HDR_SIZE + MAP_TABLE_SIZE * 32;
+/// Size in bytes of a price account, i.e., the header followed by 32 price components.
+pub const PRICE_ACCT_SIZE     : usize = PRICE_HDR_SIZE + 32 * 96;
+
+// The account structs are read directly from the account data, so a change to their layout must
+// fail to compile rather than silently corrupt parsing.
+const _: () = assert!(size_of::<Mapping>() == MAP_ACCT_SIZE);
+const _: () = assert!(size_of::<Product>() == PROD_ACCT_SIZE);
+const _: () = assert!(size_of::<Price>() == PRICE_ACCT_SIZE);
+const _: () = assert!(size_of::<PriceComp>() == 96);
+// `load_price_owned` validates prices in a buffer of `u64`s.
+const _: () = assert!(core::mem::align_of::<Price>() <= core::mem::align_of::<u64>());
+
 /// The type of Pyth account determines what data it contains
-#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum AccountType
 {
@@ -51,8 +92,27 @@ impl Default for AccountType {
   }
 }
 
+/**
+ * Maps a discriminant read from account data, which may be out of range. `PriceStatus`,
+ * `CorpAction` and `PriceType` implement `TryFrom<u32>` in the same way.
+ */
+impl TryFrom<u32> for AccountType {
+  type Error = PythError;
+
+  fn try_from(value: u32) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(AccountType::Unknown),
+      1 => Ok(AccountType::Mapping),
+      2 => Ok(AccountType::Product),
+      3 => Ok(AccountType::Price),
+      _ => Err(PythError::InvalidAccountData),
+    }
+  }
+}
+
 /// The current status of a price feed.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum PriceStatus
 {
@@ -72,9 +132,55 @@ impl Default for PriceStatus {
   }
 }
 
+impl TryFrom<u32> for PriceStatus {
+  type Error = PythError;
+
+  fn try_from(value: u32) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(PriceStatus::Unknown),
+      1 => Ok(PriceStatus::Trading),
+      2 => Ok(PriceStatus::Halted),
+      3 => Ok(PriceStatus::Auction),
+      _ => Err(PythError::InvalidAccountData),
+    }
+  }
+}
+
+impl PriceStatus {
+  /** The name of this status, as written by `Display`. */
+  fn as_str(&self) -> &'static str {
+    match self {
+      PriceStatus::Unknown => "unknown",
+      PriceStatus::Trading => "trading",
+      PriceStatus::Halted => "halted",
+      PriceStatus::Auction => "auction",
+    }
+  }
+}
+
+impl core::fmt::Display for PriceStatus {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/** Parses the names written by `Display`, ignoring ASCII case. */
+impl core::str::FromStr for PriceStatus {
+  type Err = PythError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    [PriceStatus::Unknown, PriceStatus::Trading, PriceStatus::Halted, PriceStatus::Auction]
+      .iter()
+      .copied()
+      .find(|status| s.eq_ignore_ascii_case(status.as_str()))
+      .ok_or(PythError::InvalidPriceStatus)
+  }
+}
+
 /// Status of any ongoing corporate actions.
 /// (still undergoing dev)
-#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum CorpAction
 {
@@ -87,8 +193,20 @@ impl Default for CorpAction {
   }
 }
 
+impl TryFrom<u32> for CorpAction {
+  type Error = PythError;
+
+  fn try_from(value: u32) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(CorpAction::NoCorpAct),
+      _ => Err(PythError::InvalidAccountData),
+    }
+  }
+}
+
 /// The type of prices associated with a product -- each product may have multiple price feeds of different types.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum PriceType
 {
@@ -102,8 +220,21 @@ impl Default for PriceType {
   }
 }
 
+impl TryFrom<u32> for PriceType {
+  type Error = PythError;
+
+  fn try_from(value: u32) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(PriceType::Unknown),
+      1 => Ok(PriceType::Price),
+      _ => Err(PythError::InvalidAccountData),
+    }
+  }
+}
+
 /// Public key of a Solana account
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct AccKey
 {
@@ -111,7 +242,8 @@ pub struct AccKey
 }
 
 /// Mapping accounts form a linked-list containing the listing of all products on Pyth.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Mapping
 {
@@ -128,19 +260,47 @@ pub struct Mapping
   pub unused     : u32,
   /// next mapping account (if any)
   pub next       : AccKey,
+  #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
   pub products   : [AccKey;MAP_TABLE_SIZE]
 }
 
-#[cfg(target_endian = "little")]
 unsafe impl Zeroable for Mapping {}
 
 #[cfg(target_endian = "little")]
 unsafe impl Pod for Mapping {}
 
+impl Mapping {
+  /**
+   * Iterate over the keys of the product accounts listed in this mapping account.
+   * Only the first `num` entries of `products` are considered, and invalid (all-zero) keys are skipped.
+   */
+  pub fn iter_products(&self) -> impl Iterator<Item = &AccKey> {
+    let num = (self.num as usize).min(MAP_TABLE_SIZE);
+    self.products[..num].iter().filter(|key| key.is_valid())
+  }
+}
+
+/** Summarizes `products` by the number of listed products, rather than printing the whole table. */
+impl core::fmt::Debug for Mapping {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("Mapping")
+      .field("magic", &self.magic)
+      .field("ver", &self.ver)
+      .field("atype", &self.atype)
+      .field("size", &self.size)
+      .field("num", &self.num)
+      .field("unused", &self.unused)
+      .field("next", &self.next)
+      .field("products", &format_args!("[{} products]", self.iter_products().count()))
+      .finish()
+  }
+}
+
 
 /// Product accounts contain metadata for a single product, such as its symbol ("Crypto.BTC/USD")
 /// and its base/quote currencies.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Product
 {
@@ -155,16 +315,85 @@ pub struct Product
   /// first price account in list
   pub px_acc     : AccKey,
   /// key/value pairs of reference attr.
+  #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
   pub attr       : [u8;PROD_ATTR_SIZE]
 }
 
 impl Product {
-    pub fn iter(&self) -> AttributeIter {
-        AttributeIter { attrs: &self.attr }
+  /**
+   * Iterate over the reference attributes of this product as `(key, value)` pairs.
+   * Iteration stops at the first empty key, at the end of the attribute buffer, or at the
+   * first malformed entry.
+   */
+  pub fn iter_attributes(&self) -> AttributeIter<'_> {
+    AttributeIter { attrs: &self.attr }
+  }
+
+  /** Same as `iter_attributes`. */
+  pub fn iter(&self) -> AttributeIter<'_> {
+    self.iter_attributes()
+  }
+
+  /** Get the value of the reference attribute `key`, e.g., "symbol", if present. */
+  pub fn get_attribute(&self, key: &str) -> Option<&str> {
+    self.iter_attributes().find(|(k, _)| *k == key).map(|(_, v)| v)
+  }
+
+  /** Get the symbol of this product, e.g., "Crypto.BTC/USD". */
+  pub fn symbol(&self) -> Option<&str> {
+    self.get_attribute("symbol")
+  }
+
+  /** Get the asset type of this product, e.g., "Crypto", "Equity" or "FX". */
+  pub fn asset_type(&self) -> Option<&str> {
+    self.get_attribute("asset_type")
+  }
+
+  /** Get the currency this product is quoted in, e.g., "USD". */
+  pub fn quote_currency(&self) -> Option<&str> {
+    self.get_attribute("quote_currency")
+  }
+
+  /** Get the base asset of this product, e.g., "BTC". */
+  pub fn base(&self) -> Option<&str> {
+    self.get_attribute("base")
+  }
+
+  /**
+   * Get the key of the first price account of this product, or `None` if the product has no price
+   * accounts. The remaining price accounts can be found with `Price::next_price_key`.
+   */
+  pub fn first_price_key(&self) -> Option<Pubkey> {
+    if self.px_acc.is_valid() {
+      Some(Pubkey::new_from_array(self.px_acc.val))
+    } else {
+      None
     }
+  }
+}
+
+/** Prints `attr` as a map of the reference attributes, rather than as raw bytes. */
+impl core::fmt::Debug for Product {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    struct Attributes<'a>(&'a Product);
+
+    impl core::fmt::Debug for Attributes<'_> {
+      fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_map().entries(self.0.iter_attributes()).finish()
+      }
+    }
+
+    f.debug_struct("Product")
+      .field("magic", &self.magic)
+      .field("ver", &self.ver)
+      .field("atype", &self.atype)
+      .field("size", &self.size)
+      .field("px_acc", &self.px_acc)
+      .field("attr", &Attributes(self))
+      .finish()
+  }
 }
 
-#[cfg(target_endian = "little")]
 unsafe impl Zeroable for Product {}
 
 #[cfg(target_endian = "little")]
@@ -172,7 +401,8 @@ unsafe impl Pod for Product {}
 
 /// A price and confidence at a specific slot. This struct can represent either a
 /// publisher's contribution or the outcome of price aggregation.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct PriceInfo
 {
@@ -191,8 +421,23 @@ pub struct PriceInfo
   pub pub_slot   : u64
 }
 
+impl PriceInfo {
+  /**
+   * Get this price and confidence interval with exponent `expo`, which is stored in the enclosing
+   * `Price` account. Returns `None` if the status is not `Trading`. Unlike
+   * `Price::get_current_price`, this does not check whether the price is stale.
+   */
+  pub fn to_price_conf(&self, expo: i32) -> Option<PriceConf> {
+    if self.status != PriceStatus::Trading {
+      return None;
+    }
+    Some(PriceConf { price: self.price, conf: self.conf, expo })
+  }
+}
+
 /// The price and confidence contributed by a specific publisher.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct PriceComp
 {
@@ -207,7 +452,8 @@ pub struct PriceComp
 }
 
 /// An exponentially-weighted moving average.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Ema
 {
@@ -221,6 +467,8 @@ pub struct Ema
 
 /// Price accounts represent a continuously-updating price feed for a product.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "price-borsh", derive(BorshSerialize, BorshDeserialize))]
 #[repr(C)]
 pub struct Price
 {
@@ -266,11 +514,11 @@ pub struct Price
   pub drv3           : i64,
   /// aggregate price info
   pub agg            : PriceInfo,
-  /// price components one per quoter
+  /// price components one per quoter. Only the first `num` are in use; prefer `get_component`
+  /// over indexing, which panics past the end of the array.
   pub comp           : [PriceComp;32]
 }
 
-#[cfg(target_endian = "little")]
 unsafe impl Zeroable for Price {}
 
 #[cfg(target_endian = "little")]
@@ -283,8 +531,21 @@ impl Price {
    */
   pub fn get_current_price_status(&self) -> PriceStatus {
     #[cfg(target_arch = "bpf")]
-    if matches!(self.agg.status, PriceStatus::Trading) &&
-      Clock::get().unwrap().slot - self.agg.pub_slot > MAX_SLOT_DIFFERENCE {
+    return self.get_price_status_at(Clock::get().unwrap().slot);
+    #[cfg(not(target_arch = "bpf"))]
+    self.agg.status
+  }
+
+  /**
+   * Get the status of the aggregate price at `current_slot`, which is `Unknown` if the price is
+   * trading but was published more than `MAX_SLOT_DIFFERENCE` slots before `current_slot`. This
+   * is the check performed by `get_current_price_status` on-chain, with an explicit slot.
+   *
+   * A publish slot after `current_slot` counts as zero slots old rather than overflowing.
+   */
+  pub fn get_price_status_at(&self, current_slot: u64) -> PriceStatus {
+    if self.agg.status == PriceStatus::Trading &&
+      self.get_slots_since_update(current_slot) > MAX_SLOT_DIFFERENCE {
       return PriceStatus::Unknown;
     }
     self.agg.status
@@ -293,10 +554,11 @@ impl Price {
   /**
    * Get the current price and confidence interval as fixed-point numbers of the form a * 10^e.
    * Returns a struct containing the current price, confidence interval, and the exponent for both
-   * numbers. Returns `None` if price information is currently unavailable for any reason.
+   * numbers. Returns `None` if price information is currently unavailable for any reason,
+   * including if the account has never had a valid aggregate price.
    */
   pub fn get_current_price(&self) -> Option<PriceConf> {
-    if !matches!(self.get_current_price_status(), PriceStatus::Trading) {
+    if self.valid_slot == 0 || !matches!(self.get_current_price_status(), PriceStatus::Trading) {
       None
     } else {
       Some(PriceConf {
@@ -308,164 +570,2485 @@ impl Price {
   }
 
   /**
-   * Get the exponential moving average price (ema_price) and a confidence interval on the result.
-   * Returns `None` if the ema_price is currently unavailable.
-   *
-   * At the moment, the confidence interval returned by this method is computed in
-   * a somewhat questionable way, so we do not recommend using it for high-value applications.
+   * Get the aggregate price and confidence interval while the product is in an auction, e.g., the
+   * opening auction of an equity, during which the price is informative but not tradable.
+   * Returns `None` unless the aggregate status is `PriceStatus::Auction`, so callers must opt in
+   * to auction prices explicitly; `get_current_price` never returns them.
    */
-  pub fn get_ema_price(&self) -> Option<PriceConf> {
-    // This method currently cannot return None, but may do so in the future.
-    // Note that the ema_confidence is a positive number in i64, so safe to cast to u64.
-    Some(PriceConf { price: self.ema_price.val, conf: self.ema_confidence.val as u64, expo: self.expo })
+  pub fn get_auction_price(&self) -> Option<PriceConf> {
+    if self.valid_slot == 0 || self.agg.status != PriceStatus::Auction {
+      return None;
+    }
+    Some(PriceConf { price: self.agg.price, conf: self.agg.conf, expo: self.expo })
   }
 
   /**
-   * Get the current price of this account in a different quote currency. If this account
-   * represents the price of the product X/Z, and `quote` represents the price of the product Y/Z,
-   * this method returns the price of X/Y. Use this method to get the price of e.g., mSOL/SOL from
-   * the mSOL/USD and SOL/USD accounts.
-   *
-   * `result_expo` determines the exponent of the result, i.e., the number of digits below the decimal
-   * point. This method returns `None` if either the price or confidence are too large to be
-   * represented with the requested exponent.
+   * Returns true if the account has had a valid aggregate price (`valid_slot > 0`), and the stored
+   * aggregate status is `Trading`. A newly created price account has a zero `valid_slot` and a
+   * price of zero, which must not be used.
    */
-  pub fn get_price_in_quote(&self, quote: &Price, result_expo: i32) -> Option<PriceConf> {
-    return match (self.get_current_price(), quote.get_current_price()) {
-      (Some(base_price_conf), Some(quote_price_conf)) =>
-        base_price_conf.div(&quote_price_conf)?.scale_to_exponent(result_expo),
-      (_, _) => None,
-    }
+  pub fn is_initialized_and_trading(&self) -> bool {
+    self.valid_slot > 0 && self.agg.status == PriceStatus::Trading
   }
 
   /**
-   * Get the price of a basket of currencies. Each entry in `amounts` is of the form
-   * `(price, qty, qty_expo)`, and the result is the sum of `price * qty * 10^qty_expo`.
-   * The result is returned with exponent `result_expo`.
+   * Get the status of the aggregate price exactly as stored in the account. Unlike
+   * `get_current_price_status`, this does not mark stale prices as unknown when used on-chain.
+   */
+  pub fn get_status(&self) -> PriceStatus {
+    self.agg.status
+  }
+
+  /**
+   * Get the last aggregate price and confidence interval regardless of the status of the price feed.
    *
-   * An example use case for this function is to get the value of an LP token.
+   * Warning: this method is dangerous. If the feed is not trading, the returned price may be arbitrarily
+   * stale or otherwise meaningless. Prefer `get_current_price` unless you implement your own checks
+   * on `get_status`.
    */
-  pub fn price_basket(amounts: &[(Price, i64, i32)], result_expo: i32) -> Option<PriceConf> {
-    assert!(amounts.len() > 0);
-    let mut res = PriceConf { price: 0, conf: 0, expo: result_expo };
-    for i in 0..amounts.len() {
-      res = res.add(
-        &amounts[i].0.get_current_price()?.cmul(amounts[i].1, amounts[i].2)?.scale_to_exponent(result_expo)?
-      )?
+  pub fn get_current_price_unchecked(&self) -> PriceConf {
+    PriceConf {
+      price: self.agg.price,
+      conf: self.agg.conf,
+      expo: self.expo
     }
-    Some(res)
   }
-}
 
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-struct AccKeyU64
-{
-  pub val: [u64;4]
-}
+  /**
+   * Get the aggregate price information (`agg`) as stored in the account: price, confidence,
+   * status, corporate action and publish slot. Unlike `get_current_price`, this does not check
+   * the status of the price, so callers must check it themselves before using the price.
+   */
+  pub fn get_agg_info(&self) -> &PriceInfo {
+    &self.agg
+  }
 
-#[cfg(target_endian = "little")]
-unsafe impl Zeroable for AccKeyU64 {}
+  /** Get the slot in which the aggregate price was published. Same as `get_agg_publish_slot`. */
+  pub fn get_publish_slot(&self) -> u64 {
+    self.get_agg_publish_slot()
+  }
 
-#[cfg(target_endian = "little")]
-unsafe impl Pod for AccKeyU64 {}
+  /**
+   * Get the slot in which the current aggregate price was computed (`agg.pub_slot`). This is the
+   * slot to measure the age of the price against, and the one used by the staleness checks of
+   * this crate, e.g., `get_slots_since_update` and `get_price_no_older_than`.
+   */
+  pub fn get_agg_publish_slot(&self) -> u64 {
+    self.agg.pub_slot
+  }
 
-impl AccKey
-{
-  pub fn is_valid( &self ) -> bool  {
-    match load::<AccKeyU64>( &self.val ) {
-      Ok(k8) => k8.val[0]!=0 || k8.val[1]!=0 || k8.val[2]!=0 || k8.val[3]!=0,
-      Err(_) => false,
+  /**
+   * Get the valid slot of the aggregate price (`valid_slot`). When the oracle program computes a
+   * new aggregate price, it moves the previous `agg.pub_slot` into `valid_slot`, so the valid slot
+   * lags behind `get_agg_publish_slot` and must not be used to check whether a price is stale. A
+   * valid slot of zero means that the account has never had a valid aggregate price.
+   */
+  pub fn get_valid_slot(&self) -> u64 {
+    self.valid_slot
+  }
+
+  /**
+   * Get the number of slots between the publication of the aggregate price and `current_slot`.
+   * Returns 0 if `current_slot` is before the publish slot, e.g., because `current_slot` was read
+   * from a lagging RPC node, rather than overflowing.
+   */
+  pub fn get_slots_since_update(&self, current_slot: u64) -> u64 {
+    current_slot.saturating_sub(self.get_agg_publish_slot())
+  }
+
+  /**
+   * Get the number of slots between the last valid (not unknown) aggregate price (`last_slot`)
+   * and `current_slot`, i.e., for how long the feed has not been trading. Returns 0 if
+   * `current_slot` is before `last_slot`, and `None` if the account has never had a valid
+   * aggregate price.
+   */
+  pub fn slots_since_last_trading(&self, current_slot: u64) -> Option<u64> {
+    if self.last_slot == 0 {
+      return None;
     }
+    Some(current_slot.saturating_sub(self.last_slot))
   }
-}
 
-fn load<T: Pod>(data: &[u8]) -> Result<&T, PodCastError> {
-  let size = size_of::<T>();
-  if data.len() >= size {
-    Ok(from_bytes(cast_slice::<u8, u8>(try_cast_slice(
-      &data[0..size],
-    )?)))
-  } else {
-    Err(PodCastError::SizeMismatch)
+  /**
+   * Check whether the aggregate price was published more than `max_age` slots before
+   * `current_slot`, regardless of its status. The status field is only updated when the price is,
+   * so a price can still report `PriceStatus::Trading` long after publishers have stopped
+   * updating it. Callers should treat such a stale trading price as unusable.
+   */
+  pub fn is_stale(&self, current_slot: u64, max_age: u64) -> bool {
+    self.get_slots_since_update(current_slot) > max_age
   }
-}
 
-/** Get a `Mapping` account from the raw byte value of a Solana account. */
-pub fn load_mapping(data: &[u8]) -> Result<&Mapping, PythError> {
-  let pyth_mapping = load::<Mapping>(&data).map_err(|_| PythError::InvalidAccountData)?;
+  /**
+   * Get the current price and confidence interval, provided that the aggregate price was published
+   * no more than `max_slot_age` slots before `current_slot`. Returns `None` if the price is not
+   * trading, has never had a valid aggregate price, or is older than the requested bound.
+   *
+   * Only `max_slot_age` bounds the age of the price: unlike `get_current_price`, this does not
+   * also apply the `MAX_SLOT_DIFFERENCE` cap on-chain, so a bound above it is honored.
+   */
+  pub fn get_price_no_older_than(&self, current_slot: u64, max_slot_age: u64) -> Option<PriceConf> {
+    if self.is_stale(current_slot, max_slot_age) || !self.is_initialized_and_trading() {
+      return None;
+    }
+    Some(self.get_current_price_unchecked())
+  }
 
-  if pyth_mapping.magic != MAGIC {
-    return Err(PythError::InvalidAccountData);
+  /**
+   * Get the current price and confidence interval after applying the checks that most consumers
+   * need, returning an error for the first one that fails:
+   *
+   * 1. `PythError::PriceNotTrading` if the price is not currently trading.
+   * 2. `PythError::StalePrice` if the price was published more than `max_age` slots before
+   *    `current_slot` (see `get_price_no_older_than`).
+   * 3. `PythError::ConfidenceTooWide` if the confidence interval exceeds `max_conf_bps` basis
+   *    points of the price, or the price is zero (see `get_confidence_ratio_bps`).
+   */
+  pub fn get_validated_price(
+    &self,
+    current_slot: u64,
+    max_age: u64,
+    max_conf_bps: u64,
+  ) -> Result<PriceConf, PythError> {
+    let price = self.get_current_price().ok_or(PythError::PriceNotTrading)?;
+    if self.is_stale(current_slot, max_age) {
+      return Err(PythError::StalePrice);
+    }
+    match price.get_confidence_ratio_bps() {
+      Some(ratio) if ratio <= max_conf_bps => Ok(price),
+      _ => Err(PythError::ConfidenceTooWide),
+    }
   }
-  if pyth_mapping.ver != VERSION_2 {
-    return Err(PythError::BadVersionNumber);
+
+  /**
+   * Get the current price and confidence interval, provided that at least `min` publishers
+   * contributed to the aggregate price (`num_qt >= min`). Returns `None` if the price is not
+   * currently trading or has fewer contributing publishers.
+   */
+  pub fn get_price_with_min_publishers(&self, min: u32) -> Option<PriceConf> {
+    if self.num_qt < min {
+      return None;
+    }
+    self.get_current_price()
   }
-  if pyth_mapping.atype != AccountType::Mapping as u32 {
-    return Err(PythError::WrongAccountType);
+
+  /**
+   * Get the current price and confidence interval, provided that no corporate action (such as a
+   * stock split) is in progress for the product (`agg.corp_act` is `CorpAction::NoCorpAct`).
+   * Prices of equities may be discontinuous around corporate actions, so this lets consumers skip
+   * them. Returns `None` if the price is not currently trading or a corporate action is pending.
+   *
+   * `CorpAction` currently has no other variants, and `load_price` rejects accounts with any other
+   * `corp_act` value with `PythError::InvalidAccountData`. So for a loaded account this is the same
+   * as `get_current_price` until variants for corporate actions are added to `CorpAction`.
+   */
+  pub fn get_current_price_with_corp_act_check(&self) -> Option<PriceConf> {
+    if self.agg.corp_act != CorpAction::NoCorpAct {
+      return None;
+    }
+    self.get_current_price()
   }
 
-  return Ok(pyth_mapping);
-}
+  /**
+   * Get the confidence interval of the current price as a fraction of the price, in basis points
+   * (rounded down). Returns `None` if the price is not currently trading or is zero.
+   * See `PriceConf::get_confidence_ratio_bps`.
+   */
+  pub fn get_confidence_ratio_bps(&self) -> Option<u64> {
+    self.get_current_price()?.get_confidence_ratio_bps()
+  }
 
-/** Get a `Product` account from the raw byte value of a Solana account. */
-pub fn load_product(data: &[u8]) -> Result<&Product, PythError> {
-  let pyth_product = load::<Product>(&data).map_err(|_| PythError::InvalidAccountData)?;
+  /**
+   * Get the lower and upper bounds of the confidence interval around the current price, as
+   * `(price - conf, price + conf)`. Returns `None` if the price is not currently trading or either
+   * bound does not fit into an i64. See `PriceConf::lower_bound` and `PriceConf::upper_bound`.
+   */
+  pub fn get_price_bounds(&self) -> Option<(PriceConf, PriceConf)> {
+    let price = self.get_current_price()?;
+    Some((price.lower_bound()?, price.upper_bound()?))
+  }
 
-  if pyth_product.magic != MAGIC {
-    return Err(PythError::InvalidAccountData);
+  /**
+   * Get the key of the next price account for the same product, or `None` if this is the last
+   * price account in the list. Callers can walk all price accounts of a product by fetching each
+   * key in turn, starting from `Product::first_price_key`:
+   *
+   * ```ignore
+   * let mut price = *load_price(&first_price_account_data)?;
+   * while let Some(key) = price.next_price_key() {
+   *   let data = fetch_account_data(&key);
+   *   price = *load_price(&data)?;
+   * }
+   * ```
+   */
+  pub fn next_price_key(&self) -> Option<Pubkey> {
+    if self.next.is_valid() {
+      Some(Pubkey::new_from_array(self.next.val))
+    } else {
+      None
+    }
+  }
+
+  /**
+   * Get the aggregate price and confidence interval of the update preceding the current one.
+   * Note that this price is not checked for staleness or validity.
+   */
+  pub fn get_prev_price(&self) -> PriceConf {
+    PriceConf {
+      price: self.prev_price,
+      conf: self.prev_conf,
+      expo: self.expo
+    }
   }
-  if pyth_product.ver != VERSION_2 {
-    return Err(PythError::BadVersionNumber);
+
+  /** Get the valid slot of the update preceding the current one. */
+  pub fn get_prev_slot(&self) -> u64 {
+    self.prev_slot
   }
-  if pyth_product.atype != AccountType::Product as u32 {
-    return Err(PythError::WrongAccountType);
+
+  /**
+   * Iterate over the publisher components that currently contribute a trading price. Only the
+   * first `num` entries of `comp` are considered, and components whose `agg.status` is not
+   * `Trading` are skipped.
+   */
+  pub fn iter_trading_components(&self) -> impl Iterator<Item = &PriceComp> {
+    self.components().iter().filter(|comp| comp.agg.status == PriceStatus::Trading)
   }
 
-  return Ok(pyth_product);
-}
+  /**
+   * Recompute the aggregate price from the trading publisher components. Returns `None` if no
+   * component is trading, or if the result does not fit into a `PriceConf`.
+   *
+   * Each component votes three times, at `price - conf`, `price` and `price + conf`. The price is
+   * the median of these votes (the two middle votes are averaged, rounding toward zero), and the
+   * confidence is the larger of the distances from the median to the 25th and 75th percentile
+   * votes. This approximates the aggregation performed by the oracle program but does not
+   * reproduce it exactly, so it is intended for cross-checking the aggregate price in research
+   * or monitoring, not as a substitute for `get_current_price`.
+   */
+  pub fn compute_aggregate(&self) -> Option<PriceConf> {
+    let mut votes: Vec<i128> = self
+      .iter_trading_components()
+      .flat_map(|comp| {
+        let price = comp.agg.price as i128;
+        let conf = comp.agg.conf as i128;
+        [price - conf, price, price + conf]
+      })
+      .collect();
+    if votes.is_empty() {
+      return None;
+    }
+    votes.sort_unstable();
 
-/** Get a `Price` account from the raw byte value of a Solana account. */
-pub fn load_price(data: &[u8]) -> Result<&Price, PythError> {
-  let pyth_price = load::<Price>(&data).map_err(|_| PythError::InvalidAccountData)?;
+    let n = votes.len();
+    let median = (votes[(n - 1) / 2] + votes[n / 2]) / 2;
+    let p25 = votes[n / 4];
+    let p75 = votes[n - 1 - n / 4];
+    let conf = (median - p25).max(p75 - median);
 
-  if pyth_price.magic != MAGIC {
-    return Err(PythError::InvalidAccountData);
+    Some(PriceConf {
+      price: i64::try_from(median).ok()?,
+      conf: u64::try_from(conf).ok()?,
+      expo: self.expo,
+    })
   }
-  if pyth_price.ver != VERSION_2 {
-    return Err(PythError::BadVersionNumber);
+
+  /**
+   * Get the component contributed by `publisher`, or `None` if that publisher is not among the
+   * first `num` components. Note that the returned component may not be trading.
+   */
+  pub fn get_publisher_price(&self, publisher: &Pubkey) -> Option<&PriceComp> {
+    self.components().iter().find(|comp| comp.publisher.val == publisher.to_bytes())
   }
-  if pyth_price.atype != AccountType::Price as u32 {
-    return Err(PythError::WrongAccountType);
+
+  /**
+   * Get the component at `index`, or `None` if `index` is not among the first `num` components,
+   * which are the ones in use. Unlike indexing `comp` directly, this never panics, even if `num`
+   * is corrupt, and it does not return stale entries past `num`.
+   */
+  pub fn get_component(&self, index: usize) -> Option<&PriceComp> {
+    self.components().get(index)
   }
 
-  return Ok(pyth_price);
-}
+  /**
+   * Get the price and confidence interval that the component at `index` contributed to the current
+   * aggregate price (`comp[index].agg`), with the exponent of this account. Returns `None` if
+   * `index` is not among the first `num` components or the component is not trading.
+   */
+  pub fn component_price_conf(&self, index: usize) -> Option<PriceConf> {
+    self.get_component(index)?.agg.to_price_conf(self.expo)
+  }
+
+  /**
+   * Same as `component_price_conf`, but for the latest price submitted by the component
+   * (`comp[index].latest`), which may not have been aggregated yet.
+   */
+  pub fn component_latest_conf(&self, index: usize) -> Option<PriceConf> {
+    self.get_component(index)?.latest.to_price_conf(self.expo)
+  }
 
+  /**
+   * Get the difference between the highest and the lowest price that trading components
+   * contributed to the current aggregate (`comp[i].agg`), with the exponent of this account. This
+   * measures the dispersion across publishers. The confidence is the sum of the confidences of the
+   * two extreme components, as in `PriceConf::sub`.
+   *
+   * Returns `None` if fewer than two components are trading, or the difference does not fit into
+   * an i64.
+   */
+  pub fn component_price_spread(&self) -> Option<PriceConf> {
+    let mut trading = self.components().iter().filter_map(|comp| comp.agg.to_price_conf(self.expo));
+    let first = trading.next()?;
+    let (mut min, mut max, mut count) = (first, first, 1);
+    for price in trading {
+      if price.price < min.price {
+        min = price;
+      }
+      // Ties go to the later component, so that `min` and `max` are always distinct components.
+      if price.price >= max.price {
+        max = price;
+      }
+      count += 1;
+    }
+    if count < 2 {
+      return None;
+    }
+    max.sub(&min)
+  }
 
-pub struct AttributeIter<'a> {
-    attrs: &'a [u8],
-}
+  /**
+   * Get the number of publishers whose prices make up the current aggregate price. Same as
+   * `num_quoters_in_aggregate`.
+   */
+  pub fn get_publisher_count(&self) -> u32 {
+    self.num_quoters_in_aggregate()
+  }
 
-impl<'a> Iterator for AttributeIter<'a> {
-    type Item = (&'a str, &'a str);
+  /**
+   * Get the number of component slots in use (`num`), i.e., the number of publishers permissioned
+   * to publish this price, whether or not they are currently publishing. Do not use this for
+   * quorum checks; see `num_quoters_in_aggregate`.
+   */
+  pub fn num_components(&self) -> u32 {
+    self.num
+  }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.attrs.is_empty() {
-            return None;
-        }
-        let (key, data) = get_attr_str(self.attrs);
-        let (val, data) = get_attr_str(data);
-        self.attrs = data;
-        return Some((key, val));
-    }
-}
+  /**
+   * Get the number of publishers whose prices were included in the current aggregate price
+   * (`num_qt`). This can be much smaller than `num_components`, e.g., when some publishers are
+   * offline or their prices are not trading, so quorum policies (a minimum number of publishers
+   * behind a price) should use this count.
+   */
+  pub fn num_quoters_in_aggregate(&self) -> u32 {
+    self.num_qt
+  }
 
-fn get_attr_str(buf: &[u8]) -> (&str, &[u8]) {
-    if buf.is_empty() {
-        return ("", &[]);
-    }
-    let len = buf[0] as usize;
-    let str = std::str::from_utf8(&buf[1..len + 1]).expect("attr should be ascii or utf-8");
-    let remaining_buf = &buf[len + 1..];
-    (str, remaining_buf)
+  /** Get the first `num` entries of `comp`, which are the components in use. */
+  fn components(&self) -> &[PriceComp] {
+    let num = (self.num as usize).min(self.comp.len());
+    &self.comp[..num]
+  }
+
+  /**
+   * Get the exponential moving average price (ema_price) and a confidence interval on the result.
+   * Returns `None` if the ema_price is currently unavailable.
+   *
+   * The confidence interval is `max(ema_confidence, |ema_price - agg.price|)`. The moving average
+   * of the confidence alone understates the uncertainty while the price moves quickly, so it is
+   * widened to the distance between the moving average and the latest aggregate price whenever
+   * that distance is larger.
+   */
+  pub fn get_ema_price(&self) -> Option<PriceConf> {
+    // This method currently cannot return None, but may do so in the future.
+    // Note that the ema_confidence is a positive number in i64, so safe to cast to u64.
+    Some(ema_price_conf(self.ema_price.val, self.ema_confidence.val as u64, self.agg.price, self.expo))
+  }
+
+  /**
+   * Get the current price and confidence interval, provided that the price deviates from the
+   * exponential moving average price by at most `max_deviation_bps` basis points of the moving
+   * average, i.e., `|agg.price - ema_price| * 10000 <= max_deviation_bps * |ema_price|`. A large
+   * deviation may indicate that the current price has been manipulated.
+   *
+   * Returns `None` if the price is not currently trading, the moving average is zero, or the
+   * deviation is too large.
+   */
+  pub fn get_price_if_close_to_ema(&self, max_deviation_bps: u64) -> Option<PriceConf> {
+    let price = self.get_current_price()?;
+    let ema = self.ema_price.val;
+    if ema == 0 {
+      return None;
+    }
+
+    // The deviation uses at most 65 bits, so both sides fit into 128 bits.
+    let deviation = (price.price as i128 - ema as i128).unsigned_abs();
+    if deviation * 10_000 <= max_deviation_bps as u128 * ema.unsigned_abs() as u128 {
+      Some(price)
+    } else {
+      None
+    }
+  }
+
+  /**
+   * Get the deviation of the current price from the exponential moving average price, in basis
+   * points of the moving average: `(agg.price - ema_price) * 10000 / |ema_price|`, rounded toward
+   * zero. The result is positive when the current price is above the moving average, which can be
+   * used as a simple trend signal.
+   *
+   * Returns `None` if the price is not currently trading, the moving average is zero, or the
+   * result does not fit into an i64.
+   */
+  pub fn ema_spot_ratio_bps(&self) -> Option<i64> {
+    let price = self.get_current_price()?;
+    let ema = self.ema_price.val;
+    if ema == 0 {
+      return None;
+    }
+
+    // The difference uses at most 65 bits, so the product fits into 128 bits.
+    let ratio = (price.price as i128 - ema as i128) * 10_000 / ema.unsigned_abs() as i128;
+    if ratio < i64::MIN as i128 || ratio > i64::MAX as i128 {
+      return None;
+    }
+    Some(ratio as i64)
+  }
+
+  /**
+   * Get the exponential moving average confidence as a fraction of the exponential moving average
+   * price, in basis points: `ema_confidence * 10000 / |ema_price|`, rounded down. The average
+   * confidence widens when the market is volatile, so this is a cheap proxy for volatility.
+   *
+   * Returns `None` if the moving average price is zero, the moving average confidence is negative
+   * (which a valid account never stores), or the result does not fit into a u64.
+   */
+  pub fn get_volatility_proxy_bps(&self) -> Option<u64> {
+    let ema = self.ema_price.val;
+    if ema == 0 {
+      return None;
+    }
+    let ema_conf = u64::try_from(self.ema_confidence.val).ok()?;
+
+    let ratio = ema_conf as u128 * 10_000 / ema.unsigned_abs() as u128;
+    u64::try_from(ratio).ok()
+  }
+
+  /**
+   * Get the exponent of all prices in this account, e.g., -8 if a price of 5700012000000 means
+   * 57000.12 units of the quote currency (see `Product::quote_currency`).
+   */
+  pub fn display_exponent(&self) -> i32 {
+    self.expo
+  }
+
+  /**
+   * Format the current price for display, prefixed by the symbol of `product` without its asset
+   * type, e.g., "BTC/USD: 57000.12 ± 3.40" for the product with symbol "Crypto.BTC/USD". If the
+   * price is not currently trading, its status is shown instead, e.g., "BTC/USD: halted". The
+   * symbol is shown as "unknown" if the product has none.
+   */
+  pub fn format_with_product(&self, product: &Product) -> String {
+    let symbol = product.symbol().unwrap_or("unknown");
+    // The asset type and any qualifiers precede the last '.', e.g., "Crypto." or "Equity.US.".
+    let name = symbol.rsplit('.').next().unwrap_or(symbol);
+    match self.get_current_price() {
+      Some(price) => alloc::format!("{}: {}", name, price),
+      None => alloc::format!("{}: {}", name, self.get_current_price_status()),
+    }
+  }
+
+  /**
+   * Get the current price of this account in a different quote currency. If this account
+   * represents the price of the product X/Z, and `quote` represents the price of the product Y/Z,
+   * this method returns the price of X/Y. Use this method to get the price of e.g., mSOL/SOL from
+   * the mSOL/USD and SOL/USD accounts.
+   *
+   * `result_expo` determines the exponent of the result, i.e., the number of digits below the decimal
+   * point. This method returns `None` if either price is not currently trading, or if the result
+   * cannot be computed, e.g., because the price or confidence are too large to be represented
+   * with the requested exponent. Use `get_price_in_quote_result` to find out which of these
+   * cases occurred.
+   */
+  pub fn get_price_in_quote(&self, quote: &Price, result_expo: i32) -> Option<PriceConf> {
+    self.get_price_in_quote_result(quote, result_expo).ok()
+  }
+
+  /**
+   * Same as `get_price_in_quote`, but returns an error describing why the price is unavailable:
+   * `BasePriceNotTrading` or `QuotePriceNotTrading` if the respective price is not currently
+   * trading, or `ArithmeticFailed` if the result cannot be computed (e.g., the quote price is
+   * zero, or the result is not representable with `result_expo`).
+   */
+  pub fn get_price_in_quote_result(&self, quote: &Price, result_expo: i32) -> Result<PriceConf, PythError> {
+    let base_price_conf = self.get_current_price().ok_or(PythError::BasePriceNotTrading)?;
+    let quote_price_conf = quote.get_current_price().ok_or(PythError::QuotePriceNotTrading)?;
+    base_price_conf
+      .div_with_expo(&quote_price_conf, result_expo)
+      .ok_or(PythError::ArithmeticFailed)
+  }
+
+  /** Get the aggregate price and the moving average price of this account in a single value. */
+  pub fn snapshot(&self) -> PriceSnapshot {
+    PriceSnapshot {
+      price: self.get_current_price_unchecked(),
+      // The ema_confidence is a positive number in i64, so safe to cast to u64.
+      ema: ema_price_conf(self.ema_price.val, self.ema_confidence.val as u64, self.agg.price, self.expo),
+      publish_slot: self.get_publish_slot(),
+      num_publishers: self.num_qt,
+      status: self.get_current_price_status(),
+    }
+  }
+
+  /** Copy the state of this account into an owned `PriceFeed`. */
+  pub fn to_owned_feed(&self) -> PriceFeed {
+    PriceFeed {
+      prod: self.prod,
+      expo: self.expo,
+      num_qt: self.num_qt,
+      last_slot: self.last_slot,
+      valid_slot: self.valid_slot,
+      agg: self.agg,
+      ema_price: self.ema_price.val,
+      // The ema_confidence is a positive number in i64, so safe to cast to u64.
+      ema_conf: self.ema_confidence.val as u64,
+      prev_slot: self.prev_slot,
+      prev_price: self.prev_price,
+      prev_conf: self.prev_conf,
+    }
+  }
+
+  /**
+   * Get the value of `qty * 10^qty_expo` units of this asset, with exponent `result_expo`. The
+   * quantity may be negative, e.g., to value a short position.
+   *
+   * Returns `None` if the price is not currently trading or the value cannot be computed.
+   */
+  pub fn get_position_value(&self, qty: i64, qty_expo: i32, result_expo: i32) -> Option<PriceConf> {
+    self.get_current_price()?.cmul(qty, qty_expo)?.scale_to_exponent(result_expo)
+  }
+
+  /**
+   * Get the price of a basket of currencies. Each entry in `amounts` is of the form
+   * `(price, qty, qty_expo)`, and the result is the sum of `price * qty * 10^qty_expo`.
+   * The result is returned with exponent `result_expo`.
+   *
+   * An example use case for this function is to get the value of an LP token; see
+   * `lp_token_price`.
+   *
+   * Returns `None` if `amounts` is empty, any price is not currently trading, or the result cannot
+   * be computed. Use `price_basket_checked` to find out which entry failed.
+   */
+  pub fn price_basket(amounts: &[(Price, i64, i32)], result_expo: i32) -> Option<PriceConf> {
+    Price::price_basket_checked(amounts, result_expo).ok()
+  }
+
+  /**
+   * Same as `price_basket`, except that the error names the entry of `amounts` that could not be
+   * processed and the operation that failed.
+   */
+  pub fn price_basket_checked(amounts: &[(Price, i64, i32)], result_expo: i32) -> Result<PriceConf, BasketError> {
+    if amounts.is_empty() {
+      return Err(BasketError::EmptyBasket);
+    }
+
+    let mut res = PriceConf { price: 0, conf: 0, expo: result_expo };
+    for (index, (price, qty, qty_expo)) in amounts.iter().enumerate() {
+      let failed = |operation| BasketError::ArithmeticFailed { index, operation };
+      let value = price
+        .get_current_price()
+        .ok_or(BasketError::PriceNotTrading { index })?
+        .cmul(*qty, *qty_expo)
+        .ok_or_else(|| failed(BasketOperation::Mul))?
+        .scale_to_exponent(result_expo)
+        .ok_or_else(|| failed(BasketOperation::Scale))?;
+      res.add_in_place(&value).ok_or_else(|| failed(BasketOperation::Add))?;
+    }
+    Ok(res)
+  }
+
+  /**
+   * Get the price of one LP token of a pool holding `reserves`, given that
+   * `total_lp_supply * 10^supply_expo` LP tokens are outstanding. `reserves` has the same form as
+   * in `price_basket`, and the result is the value of the basket divided by the supply, with
+   * exponent `result_expo`. The confidence of the result is the confidence of the basket divided
+   * by the supply.
+   *
+   * The value of the basket is computed with exponent `result_expo` and then divided by the supply
+   * in a single step (see `PriceConf::div_to_exponent`). Each entry of the basket is rounded to
+   * `result_expo`, so the value of the basket is off by less than one unit per entry. As long as at
+   * least one LP token is outstanding, the division does not magnify that error and adds at most one
+   * more unit, so the result is within `reserves.len() + 1` units of `result_expo` of the exact price.
+   *
+   * Returns `None` if the supply is zero or negative, `reserves` is empty, any price is not
+   * currently trading, or the result cannot be computed.
+   */
+  pub fn lp_token_price(
+    reserves: &[(Price, i64, i32)],
+    total_lp_supply: i64,
+    supply_expo: i32,
+    result_expo: i32,
+  ) -> Option<PriceConf> {
+    if total_lp_supply <= 0 {
+      return None;
+    }
+    let supply = PriceConf { price: total_lp_supply, conf: 0, expo: supply_expo };
+    Price::price_basket(reserves, result_expo)?.div_to_exponent(&supply, result_expo)
+  }
+}
+
+/**
+ * An owned copy of the state of a `Price` account. Unlike the `Price` returned by `load_price`,
+ * a `PriceFeed` does not borrow the account data, so it can be kept after that data is dropped,
+ * e.g., to store price snapshots fetched over RPC.
+ */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceFeed
+{
+  /// product account key
+  pub prod           : AccKey,
+  /// price exponent
+  pub expo           : i32,
+  /// number of quoters that make up aggregate
+  pub num_qt         : u32,
+  /// slot of last valid (not unknown) aggregate price
+  pub last_slot      : u64,
+  /// valid slot-time of agg. price
+  pub valid_slot     : u64,
+  /// aggregate price info
+  pub agg            : PriceInfo,
+  /// exponential moving average price
+  pub ema_price      : i64,
+  /// exponential moving average confidence interval
+  pub ema_conf       : u64,
+  /// valid slot of previous update
+  pub prev_slot      : u64,
+  /// aggregate price of previous update
+  pub prev_price     : i64,
+  /// confidence interval of previous update
+  pub prev_conf      : u64,
+}
+
+impl PriceFeed {
+  /**
+   * Get the aggregate price and confidence interval, or `None` if the aggregate price is not
+   * trading or has never been valid. Unlike `Price::get_current_price`, this never checks the clock for staleness, since
+   * the feed may have been captured at any point in the past; compare `agg.pub_slot` against the
+   * current slot if necessary.
+   */
+  pub fn get_current_price(&self) -> Option<PriceConf> {
+    if self.valid_slot == 0 || !matches!(self.agg.status, PriceStatus::Trading) {
+      None
+    } else {
+      Some(PriceConf { price: self.agg.price, conf: self.agg.conf, expo: self.expo })
+    }
+  }
+
+  /** Get the exponential moving average price and confidence interval. See `Price::get_ema_price`. */
+  pub fn get_ema_price(&self) -> Option<PriceConf> {
+    Some(ema_price_conf(self.ema_price, self.ema_conf, self.agg.price, self.expo))
+  }
+
+  /** Get the aggregate price and confidence interval of the previous update. */
+  pub fn get_prev_price(&self) -> PriceConf {
+    PriceConf { price: self.prev_price, conf: self.prev_conf, expo: self.expo }
+  }
+}
+
+/**
+ * The aggregate price and moving average price of a `Price` account at one point in time, as
+ * returned by `Price::snapshot`.
+ *
+ * `price` is not checked for validity: consult `status` before using it, as in
+ * `Price::get_current_price_unchecked`.
+ */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceSnapshot
+{
+  /// aggregate price and confidence interval
+  pub price          : PriceConf,
+  /// exponential moving average price and confidence interval, see `Price::get_ema_price`
+  pub ema            : PriceConf,
+  /// slot in which the aggregate price was published
+  pub publish_slot   : u64,
+  /// number of publishers that make up the aggregate price
+  pub num_publishers : u32,
+  /// status of the aggregate price, see `Price::get_current_price_status`
+  pub status         : PriceStatus,
+}
+
+/**
+ * Helper function to compute the moving average price with a confidence interval widened to the
+ * distance between the moving average and the latest aggregate price. See `Price::get_ema_price`.
+ */
+fn ema_price_conf(ema_price: i64, ema_conf: u64, agg_price: i64, expo: i32) -> PriceConf {
+  // The distance between two i64 values always fits in a u64.
+  let spread = (ema_price as i128 - agg_price as i128).unsigned_abs() as u64;
+  PriceConf { price: ema_price, conf: ema_conf.max(spread), expo }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct AccKeyU64
+{
+  pub val: [u64;4]
+}
+
+unsafe impl Zeroable for AccKeyU64 {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for AccKeyU64 {}
+
+impl AccKey
+{
+  pub fn is_valid( &self ) -> bool  {
+    // Compared bytewise, as a key is not necessarily aligned for reading it as u64s.
+    self.val.iter().any(|b| *b != 0)
+  }
+}
+
+/**
+ * Account structs are borrowed directly from the account data on little-endian targets. On
+ * big-endian targets the fields must be byte-swapped, so the loaders return an owned copy instead.
+ */
+#[cfg(target_endian = "little")]
+type AccountRef<'a, T> = &'a T;
+#[cfg(target_endian = "big")]
+type AccountRef<'a, T> = T;
+
+/**
+ * Read a `T` from the start of `data`. Returns `PythError::AccountTooSmall` if `data` is shorter
+ * than `T`, and `PythError::MisalignedAccountData` if `data` is not aligned for `T`. Any data after
+ * the first `size_of::<T>()` bytes is ignored.
+ */
+#[cfg(target_endian = "little")]
+fn load<T: Pod>(data: &[u8]) -> Result<&T, PythError> {
+  let size = size_of::<T>();
+  let too_small = PythError::AccountTooSmall { expected: size, actual: data.len() };
+  let prefix = data.get(..size).ok_or_else(|| too_small.clone())?;
+  try_from_bytes(prefix).map_err(|e| match e {
+    PodCastError::TargetAlignmentGreaterAndInputNotAligned => PythError::MisalignedAccountData,
+    PodCastError::SizeMismatch => too_small,
+    // Only returned by slice and allocation casts, which are not used here.
+    PodCastError::OutputSliceWouldHaveSlop | PodCastError::AlignmentMismatch => PythError::InvalidAccountData,
+  })
+}
+
+#[cfg(target_endian = "big")]
+fn load<T: endian::FromLeBytes>(data: &[u8]) -> Result<T, PythError> {
+  let size = size_of::<T>();
+  if data.len() >= size {
+    T::from_le_bytes(data).ok_or(PythError::InvalidAccountData)
+  } else {
+    Err(PythError::AccountTooSmall { expected: size, actual: data.len() })
+  }
+}
+
+/**
+ * Read the version from the header shared by all Pyth accounts. The header is read separately
+ * from the rest of the account, so that accounts with a newer layout can be recognized.
+ */
+fn load_version(data: &[u8]) -> Result<u32, PythError> {
+  let header_word = |i: usize| -> Result<u32, PythError> {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(data.get(4 * i..4 * (i + 1)).ok_or(PythError::InvalidAccountData)?);
+    Ok(u32::from_le_bytes(word))
+  };
+
+  if header_word(0)? != MAGIC {
+    return Err(PythError::InvalidAccountData);
+  }
+  header_word(1)
+}
+
+/**
+ * Check the enum fields of a `Price` account before the account data is cast to a `Price`, since a
+ * value outside the range of an enum's discriminants is undefined behavior. Each field is read as a
+ * u32 at its offset in the account data. `data` must hold at least `size_of::<Price>()` bytes.
+ */
+#[cfg(target_endian = "little")]
+fn check_price_discriminants(data: &[u8]) -> Result<(), PythError> {
+  use core::mem::offset_of;
+
+  let word = |offset: usize| -> Result<u32, PythError> {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(data.get(offset..offset + 4).ok_or(PythError::InvalidAccountData)?);
+    Ok(u32::from_le_bytes(word))
+  };
+  // The status and corp_act fields of a PriceInfo starting at `offset`.
+  let check_price_info = |offset: usize| -> Result<(), PythError> {
+    PriceStatus::try_from(word(offset + offset_of!(PriceInfo, status))?)?;
+    CorpAction::try_from(word(offset + offset_of!(PriceInfo, corp_act))?)?;
+    Ok(())
+  };
+
+  PriceType::try_from(word(offset_of!(Price, ptype))?)?;
+  check_price_info(offset_of!(Price, agg))?;
+  for i in 0..32 {
+    let comp = offset_of!(Price, comp) + i * size_of::<PriceComp>();
+    check_price_info(comp + offset_of!(PriceComp, agg))?;
+    check_price_info(comp + offset_of!(PriceComp, latest))?;
+  }
+  Ok(())
+}
+
+/**
+ * Check the `size` field of an account, which is the number of bytes in use. It must cover at least
+ * the header of the account, and cannot exceed the length of the account data.
+ */
+fn check_size(size: u32, min_size: usize, data: &[u8]) -> Result<(), PythError> {
+  let size = size as usize;
+  if size < min_size || size > data.len() {
+    return Err(PythError::InvalidAccountData);
+  }
+  Ok(())
+}
+
+/**
+ * Get a `Mapping` account from the raw byte value of a Solana account. As with the other loaders,
+ * `data` may be longer than the account struct, e.g., because the account was allocated with extra
+ * space: only the prefix holding the struct is read, and the trailing data is ignored.
+ */
+pub fn load_mapping(data: &[u8]) -> Result<AccountRef<'_, Mapping>, PythError> {
+  let pyth_mapping = match load_version(data)? {
+    VERSION_2 => load::<Mapping>(&data)?,
+    found => return Err(PythError::UnsupportedVersion { found, max_supported: VERSION }),
+  };
+
+  if pyth_mapping.atype != AccountType::Mapping as u32 {
+    return Err(PythError::WrongAccountType);
+  }
+  check_size(pyth_mapping.size, MAP_HDR_SIZE, data)?;
+
+  return Ok(pyth_mapping);
+}
+
+/**
+ * Get a `Product` account from the raw byte value of a Solana account. Trailing data after the
+ * struct is ignored, as in `load_mapping`.
+ */
+pub fn load_product(data: &[u8]) -> Result<AccountRef<'_, Product>, PythError> {
+  let pyth_product = match load_version(data)? {
+    VERSION_2 => load::<Product>(&data)?,
+    found => return Err(PythError::UnsupportedVersion { found, max_supported: VERSION }),
+  };
+
+  if pyth_product.atype != AccountType::Product as u32 {
+    return Err(PythError::WrongAccountType);
+  }
+  check_size(pyth_product.size, PROD_HDR_SIZE, data)?;
+
+  return Ok(pyth_product);
+}
+
+/**
+ * Get a `Price` account from the raw byte value of a Solana account. Trailing data after the
+ * struct is ignored, as in `load_mapping`.
+ */
+pub fn load_price(data: &[u8]) -> Result<AccountRef<'_, Price>, PythError> {
+  let pyth_price = match load_version(data)? {
+    VERSION_2 => {
+      #[cfg(target_endian = "little")]
+      if data.len() >= size_of::<Price>() {
+        check_price_discriminants(data)?;
+      }
+      load::<Price>(&data)?
+    }
+    found => return Err(PythError::UnsupportedVersion { found, max_supported: VERSION }),
+  };
+
+  if pyth_price.atype != AccountType::Price as u32 {
+    return Err(PythError::WrongAccountType);
+  }
+  check_size(pyth_price.size, PRICE_HDR_SIZE, data)?;
+  if pyth_price.num as usize > pyth_price.comp.len() {
+    return Err(PythError::InvalidAccountData);
+  }
+
+  return Ok(pyth_price);
+}
+
+/**
+ * Get a `Price` account from the raw byte value of a Solana account as in `load_price`, and check
+ * that it belongs to `expected_product` (the `prod` field). Returns `PythError::ProductMismatch`
+ * otherwise. On-chain programs that expect the price of a specific product should use this, so
+ * that a caller cannot substitute the price account of another product.
+ */
+pub fn load_price_for_product<'a>(
+  price_data: &'a [u8],
+  expected_product: &Pubkey,
+) -> Result<AccountRef<'a, Price>, PythError> {
+  let price = load_price(price_data)?;
+  if price.prod.val != expected_product.to_bytes() {
+    return Err(PythError::ProductMismatch);
+  }
+  Ok(price)
+}
+
+/**
+ * Get a `Price` account from a Solana `AccountInfo`, checking that the account is owned by
+ * `program_id`, the Pyth oracle program on the cluster in use. The account data is then validated
+ * as in `load_price`, and the returned `Ref` keeps the data borrowed while the price is in use.
+ *
+ * This is the recommended way for on-chain programs to read prices. The account data alone does
+ * not prove that a price came from Pyth: anyone can create an account holding a well-formed price
+ * with any value, so `load_price` must only be used on accounts whose owner has been checked.
+ * Returns `PythError::WrongOwner` if the owner does not match.
+ */
+#[cfg(target_endian = "little")]
+pub fn load_price_account_info<'a>(
+  account: &'a AccountInfo,
+  program_id: &Pubkey,
+) -> Result<Ref<'a, Price>, PythError> {
+  if account.owner != program_id {
+    return Err(PythError::WrongOwner);
+  }
+  let data = account.try_borrow_data().map_err(|_| PythError::InvalidAccountData)?;
+  load_price(&data)?;
+  Ok(Ref::map(data, |data| from_bytes(&data[..size_of::<Price>()])))
+}
+
+/**
+ * Same as `load_price_account_info`, except that the `Price` is copied out of the account data,
+ * so the data is no longer borrowed once this function returns. This avoids holding a `Ref` to
+ * the account data (which would prevent mutably borrowing the account, e.g., to pass it to another
+ * program), at the cost of copying the account. It is also available on big-endian targets.
+ *
+ * A typical on-chain program that does not use a framework reads its price accounts like this:
+ *
+ * ```
+ * use pyth_client::{load_price_account_info_owned, PriceConf, PythError};
+ * use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+ *
+ * fn read_price(accounts: &[AccountInfo], pyth_program_id: &Pubkey) -> Result<PriceConf, PythError> {
+ *   let price = load_price_account_info_owned(&accounts[0], pyth_program_id)?;
+ *   price.get_current_price().ok_or(PythError::InvalidAccountData)
+ * }
+ * ```
+ */
+pub fn load_price_account_info_owned(account: &AccountInfo, program_id: &Pubkey) -> Result<Price, PythError> {
+  if account.owner != program_id {
+    return Err(PythError::WrongOwner);
+  }
+  let data = account.try_borrow_data().map_err(|_| PythError::InvalidAccountData)?;
+  let price = load_price(&data)?;
+  // `load_price` returns a reference into the data on little-endian targets.
+  #[cfg(target_endian = "little")]
+  let price = *price;
+  Ok(price)
+}
+
+/**
+ * A `Price` account that owns its account data, as returned by `load_price_owned`. Dereferences to
+ * a copy of the validated `Price`, so that it does not depend on the alignment of the data.
+ */
+#[cfg(target_endian = "little")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedPrice {
+  price: Price,
+  data: Vec<u8>,
+}
+
+#[cfg(target_endian = "little")]
+impl OwnedPrice {
+  /** Get the account data, which may extend beyond the end of the `Price`. */
+  pub fn data(&self) -> &[u8] {
+    &self.data
+  }
+
+  /** Take back the account data. */
+  pub fn into_data(self) -> Vec<u8> {
+    self.data
+  }
+}
+
+#[cfg(target_endian = "little")]
+impl core::ops::Deref for OwnedPrice {
+  type Target = Price;
+
+  fn deref(&self) -> &Price {
+    &self.price
+  }
+}
+
+/**
+ * Get a `Price` account from the raw byte value of a Solana account, taking ownership of the data.
+ * The account is validated exactly as in `load_price`. This is useful when the account data comes
+ * from an RPC client, and the price must outlive the scope of the local variable holding the data.
+ * Unlike `load_price`, the data may have any alignment.
+ */
+#[cfg(target_endian = "little")]
+pub fn load_price_owned(data: Vec<u8>) -> Result<OwnedPrice, PythError> {
+  // A `Vec<u8>` is only guaranteed to be byte-aligned, so validate a copy in a buffer of `u64`s,
+  // which is aligned for `Price`.
+  let mut aligned = alloc::vec![0u64; data.len() / 8 + 1];
+  bytemuck::cast_slice_mut::<u64, u8>(&mut aligned)[..data.len()].copy_from_slice(&data);
+  let price = *load_price(&bytemuck::cast_slice::<u64, u8>(&aligned)[..data.len()])?;
+  Ok(OwnedPrice { price, data })
+}
+
+/**
+ * Get a `Price` account from its base64-encoded account data, as returned by the `getAccountInfo`
+ * RPC method with the "base64" encoding. The decoded account is validated as in `load_price`.
+ * Returns `PythError::InvalidBase64` if `s` is not valid base64.
+ */
+#[cfg(all(feature = "base64", target_endian = "little"))]
+pub fn load_price_base64(s: &str) -> Result<OwnedPrice, PythError> {
+  use base64::Engine;
+
+  let data = base64::engine::general_purpose::STANDARD
+    .decode(s)
+    .map_err(|_| PythError::InvalidBase64)?;
+  load_price_owned(data)
+}
+
+/**
+ * Get all price accounts of `product`, in the order of the linked list starting at `product.px_acc`.
+ * Each price account links to the next one through `Price.next`, until a price account whose `next`
+ * is all zeros. `fetch` is called with the key of each price account and must return its raw account
+ * data, which is validated as in `load_price`.
+ *
+ * Returns `PythError::InvalidAccountData` if `fetch` returns `None` or the list contains a cycle.
+ */
+#[cfg(target_endian = "little")]
+pub fn price_accounts<F>(product: &Product, mut fetch: F) -> Result<Vec<OwnedPrice>, PythError>
+where
+  F: FnMut(Pubkey) -> Option<Vec<u8>>,
+{
+  let mut prices: Vec<OwnedPrice> = Vec::new();
+  let mut visited: Vec<Pubkey> = Vec::new();
+  let mut next = product.px_acc;
+  while next.is_valid() {
+    let key = Pubkey::new_from_array(next.val);
+    if visited.contains(&key) {
+      return Err(PythError::InvalidAccountData);
+    }
+    visited.push(key);
+
+    let price = load_price_owned(fetch(key).ok_or(PythError::InvalidAccountData)?)?;
+    next = price.next;
+    prices.push(price);
+  }
+  Ok(prices)
+}
+
+/**
+ * Get an owned `PriceFeed` from the raw byte value of a Solana price account. The account is
+ * validated exactly as in `load_price`. On-chain programs should prefer `load_price`, which does
+ * not copy the account.
+ */
+pub fn load_price_feed(data: &[u8]) -> Result<PriceFeed, PythError> {
+  load_price(data).map(|price| price.to_owned_feed())
+}
+
+/**
+ * Get the keys of all product accounts listed in the chain of mapping accounts starting at `first`.
+ * Each mapping account links to the next one through `Mapping.next`, until a mapping account whose
+ * `next` is all zeros. `fetch` is called with the key of each subsequent mapping account and must
+ * return its raw account data, which is validated as in `load_mapping`.
+ *
+ * Returns `PythError::InvalidAccountData` if `fetch` returns `None` or the chain contains a cycle.
+ */
+pub fn products_across_chain<F>(first: &Mapping, mut fetch: F) -> Result<Vec<Pubkey>, PythError>
+where
+  F: FnMut(Pubkey) -> Option<Vec<u8>>,
+{
+  let mut products: Vec<Pubkey> =
+    first.iter_products().map(|key| Pubkey::new_from_array(key.val)).collect();
+  let mut visited: Vec<Pubkey> = Vec::new();
+  let mut next = first.next;
+  while next.is_valid() {
+    let key = Pubkey::new_from_array(next.val);
+    if visited.contains(&key) {
+      return Err(PythError::InvalidAccountData);
+    }
+    visited.push(key);
+
+    let data = fetch(key).ok_or(PythError::InvalidAccountData)?;
+    let mapping = load_mapping(&data)?;
+    products.extend(mapping.iter_products().map(|key| Pubkey::new_from_array(key.val)));
+    next = mapping.next;
+  }
+  Ok(products)
+}
+
+
+/**
+ * Iterator over the key/value attributes of a `Product`. Each key and value is stored as a
+ * single length byte followed by that many bytes of UTF-8.
+ */
+pub struct AttributeIter<'a> {
+  attrs: &'a [u8],
+}
+
+impl<'a> Iterator for AttributeIter<'a> {
+  type Item = (&'a str, &'a str);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    // Consume the buffer up front so that iteration ends for good on a malformed entry.
+    let attrs = core::mem::take(&mut self.attrs);
+    let (key, data) = get_attr_str(attrs)?;
+    if key.is_empty() {
+      return None;
+    }
+    let (val, data) = get_attr_str(data)?;
+    self.attrs = data;
+    Some((key, val))
+  }
+}
+
+fn get_attr_str(buf: &[u8]) -> Option<(&str, &[u8])> {
+  let (&len, buf) = buf.split_first()?;
+  let len = len as usize;
+  if buf.len() < len {
+    return None;
+  }
+  let str = core::str::from_utf8(&buf[..len]).ok()?;
+  Some((str, &buf[len..]))
+}
+
+#[cfg(test)]
+mod test {
+  use std::mem::size_of;
+  use bytemuck::Zeroable;
+  use solana_program::pubkey::Pubkey;
+  use crate::{
+    AccKey, AccountType, Mapping, Price, PriceComp, PriceConf, PriceInfo, PriceStatus, Product,
+    PythError, MAGIC, MAP_TABLE_SIZE, PROD_ATTR_SIZE, PROD_HDR_SIZE, VERSION_2,
+  };
+
+  fn product_with_attrs(attrs: &[(&str, &str)]) -> Product {
+    let mut product = Product::zeroed();
+    let mut i = 0;
+    for s in attrs.iter().flat_map(|(k, v)| [k, v]) {
+      product.attr[i] = s.len() as u8;
+      product.attr[i + 1..i + 1 + s.len()].copy_from_slice(s.as_bytes());
+      i += 1 + s.len();
+    }
+    product
+  }
+
+  fn trading_price(price: i64, conf: u64, expo: i32, pub_slot: u64) -> Price {
+    Price {
+      expo,
+      valid_slot: pub_slot,
+      agg: PriceInfo { price, conf, status: PriceStatus::Trading, pub_slot, ..Default::default() },
+      ..Default::default()
+    }
+  }
+
+  /// A `trading_price` of `price` at exponent -2 with an EMA price of `ema`.
+  fn with_ema(price: i64, ema: i64) -> Price {
+    let mut price = trading_price(price, 1, -2, 1000);
+    price.ema_price.val = ema;
+    price
+  }
+
+  /// A publisher component whose aggregate price has the given values.
+  fn comp(price: i64, conf: u64, status: PriceStatus) -> PriceComp {
+    PriceComp { agg: PriceInfo { price, conf, status, ..Default::default() }, ..Default::default() }
+  }
+
+  /// A price account with a valid header that passes `load_price`, trading at `price` with
+  /// confidence 1 and exponent -2.
+  fn valid_price_account(price: i64) -> Price {
+    crate::test_utils::PriceBuilder::new().with_price(price, 1).with_expo(-2).with_publish_slot(1000).build()
+  }
+
+  #[test]
+  fn test_price_status_strings() {
+    for &status in &[PriceStatus::Unknown, PriceStatus::Trading, PriceStatus::Halted, PriceStatus::Auction] {
+      assert_eq!(status.to_string().parse::<PriceStatus>(), Ok(status));
+    }
+    assert_eq!(PriceStatus::Trading.to_string(), "trading");
+    assert_eq!("Halted".parse::<PriceStatus>(), Ok(PriceStatus::Halted));
+    assert_eq!("AUCTION".parse::<PriceStatus>(), Ok(PriceStatus::Auction));
+
+    assert_eq!("".parse::<PriceStatus>(), Err(PythError::InvalidPriceStatus));
+    assert_eq!(" trading".parse::<PriceStatus>(), Err(PythError::InvalidPriceStatus));
+    assert_eq!("tradin".parse::<PriceStatus>(), Err(PythError::InvalidPriceStatus));
+  }
+
+  #[test]
+  fn test_get_slots_since_update() {
+    let price = trading_price(100, 1, -2, 100);
+    assert_eq!(price.get_publish_slot(), 100);
+    assert_eq!(price.get_slots_since_update(100), 0);
+    assert_eq!(price.get_slots_since_update(125), 25);
+    assert_eq!(price.get_slots_since_update(u64::MAX), u64::MAX - 100);
+
+    // As in the stale_price test, a publish slot ahead of the current slot must not overflow.
+    assert_eq!(price.get_slots_since_update(5), 0);
+    assert_eq!(price.get_slots_since_update(0), 0);
+  }
+
+  #[test]
+  fn test_get_auction_price() {
+    let mut price = trading_price(12345, 267, -2, 1000);
+    assert_eq!(price.get_auction_price(), None);
+
+    price.agg.status = PriceStatus::Auction;
+    assert_eq!(price.get_auction_price(), Some(PriceConf { price: 12345, conf: 267, expo: -2 }));
+    assert_eq!(price.get_current_price(), None);
+
+    price.agg.status = PriceStatus::Halted;
+    assert_eq!(price.get_auction_price(), None);
+
+    // A new account has never had a valid price.
+    price.agg.status = PriceStatus::Auction;
+    price.valid_slot = 0;
+    assert_eq!(price.get_auction_price(), None);
+  }
+
+  #[test]
+  fn test_slots_since_last_trading() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    assert_eq!(price.slots_since_last_trading(1000), None);
+
+    price.last_slot = 1000;
+    assert_eq!(price.slots_since_last_trading(1000), Some(0));
+    assert_eq!(price.slots_since_last_trading(1250), Some(250));
+    assert_eq!(price.slots_since_last_trading(900), Some(0));
+
+    // A halted feed keeps the slot of its last valid price.
+    price.agg.status = PriceStatus::Halted;
+    price.agg.pub_slot = 2000;
+    assert_eq!(price.slots_since_last_trading(2000), Some(1000));
+
+    price.last_slot = 1;
+    assert_eq!(price.slots_since_last_trading(u64::MAX), Some(u64::MAX - 1));
+  }
+
+  #[test]
+  fn test_is_stale() {
+    // Fresh and trading
+    let price = trading_price(100, 1, -2, 1000);
+    assert!(!price.is_stale(1000, 25));
+    assert!(!price.is_stale(1025, 25));
+    assert!(!price.is_stale(900, 25));
+    assert!(price.get_price_no_older_than(1025, 25).is_some());
+
+    // Stale, but the status still says trading
+    assert!(price.is_stale(1026, 25));
+    assert!(price.is_stale(u64::MAX, 25));
+    assert_eq!(price.agg.status, PriceStatus::Trading);
+    assert_eq!(price.get_price_no_older_than(1026, 25), None);
+
+    // The status is not consulted.
+    let mut halted = price;
+    halted.agg.status = PriceStatus::Halted;
+    assert!(!halted.is_stale(1025, 25));
+    assert!(halted.is_stale(1026, 25));
+  }
+
+  #[test]
+  fn test_get_agg_info() {
+    let mut price = trading_price(12345, 267, -2, 1000);
+    price.agg.status = PriceStatus::Auction;
+    let info = *price.get_agg_info();
+    assert_eq!(info.price, 12345);
+    assert_eq!(info.conf, 267);
+    assert_eq!(info.status, PriceStatus::Auction);
+    assert_eq!(info.corp_act, crate::CorpAction::NoCorpAct);
+    assert_eq!(info.pub_slot, 1000);
+    assert_eq!(info, price.agg);
+
+    // The snapshot does not change with the account.
+    price.agg.price = 1;
+    assert_ne!(info, *price.get_agg_info());
+  }
+
+  #[test]
+  fn test_publish_and_valid_slots() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.valid_slot = 990;
+    assert_eq!(price.get_agg_publish_slot(), 1000);
+    assert_eq!(price.get_publish_slot(), 1000);
+    assert_eq!(price.get_valid_slot(), 990);
+
+    // Staleness is measured from the publish slot, not the valid slot.
+    assert_eq!(price.get_slots_since_update(1010), 10);
+    assert!(price.get_price_no_older_than(1010, 10).is_some());
+    assert!(price.get_price_no_older_than(1011, 10).is_none());
+    assert_eq!(price.get_price_status_at(1000 + crate::MAX_SLOT_DIFFERENCE), PriceStatus::Trading);
+    assert_eq!(price.get_validated_price(1010, 10, u64::MAX).map(|_| ()), Ok(()));
+  }
+
+  #[test]
+  fn test_get_price_status_at() {
+    let price = trading_price(100, 1, -2, 100);
+    assert_eq!(price.get_price_status_at(100), PriceStatus::Trading);
+    assert_eq!(price.get_price_status_at(100 + crate::MAX_SLOT_DIFFERENCE), PriceStatus::Trading);
+    assert_eq!(price.get_price_status_at(101 + crate::MAX_SLOT_DIFFERENCE), PriceStatus::Unknown);
+    assert_eq!(price.get_price_status_at(u64::MAX), PriceStatus::Unknown);
+
+    // The underflow boundary: a publish slot ahead of the current slot is not stale.
+    assert_eq!(price.get_price_status_at(99), PriceStatus::Trading);
+    assert_eq!(price.get_price_status_at(0), PriceStatus::Trading);
+    let mut latest = price;
+    latest.agg.pub_slot = u64::MAX;
+    assert_eq!(latest.get_price_status_at(0), PriceStatus::Trading);
+
+    // Only trading prices become stale.
+    let mut halted = price;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_price_status_at(u64::MAX), PriceStatus::Halted);
+  }
+
+  #[test]
+  fn test_get_price_no_older_than() {
+    let price = trading_price(100, 1, -2, 1000);
+    let expected = Some(PriceConf { price: 100, conf: 1, expo: -2 });
+
+    assert_eq!(price.get_price_no_older_than(1000, 0), expected);
+    assert_eq!(price.get_price_no_older_than(1010, 10), expected);
+    assert_eq!(price.get_price_no_older_than(1011, 10), None);
+    // Bounds above MAX_SLOT_DIFFERENCE (25 slots) are honored.
+    assert_eq!(price.get_price_no_older_than(1100, 100), expected);
+    assert_eq!(price.get_price_no_older_than(1101, 100), None);
+
+    // current_slot behind the publish slot must not underflow
+    assert_eq!(price.get_price_no_older_than(900, 0), expected);
+
+    let mut halted = price;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_price_no_older_than(1000, 10), None);
+    let mut never_valid = price;
+    never_valid.valid_slot = 0;
+    assert_eq!(never_valid.get_price_no_older_than(1000, 10), None);
+  }
+
+  #[test]
+  fn test_get_price_with_min_publishers() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.num_qt = 3;
+    let expected = Some(PriceConf { price: 100, conf: 1, expo: -2 });
+
+    assert_eq!(price.get_price_with_min_publishers(0), expected);
+    assert_eq!(price.get_price_with_min_publishers(2), expected);
+    assert_eq!(price.get_price_with_min_publishers(3), expected);
+    assert_eq!(price.get_price_with_min_publishers(4), None);
+    assert_eq!(price.get_price_with_min_publishers(u32::MAX), None);
+
+    price.agg.status = PriceStatus::Halted;
+    assert_eq!(price.get_price_with_min_publishers(3), None);
+  }
+
+  #[test]
+  fn test_get_price_if_close_to_ema() {
+    // A band of 1% around an EMA of 100.00.
+    assert_eq!(
+      with_ema(10_100, 10_000).get_price_if_close_to_ema(100),
+      Some(PriceConf { price: 10_100, conf: 1, expo: -2 })
+    );
+    assert_eq!(with_ema(10_101, 10_000).get_price_if_close_to_ema(100), None);
+    assert!(with_ema(9_900, 10_000).get_price_if_close_to_ema(100).is_some());
+    assert_eq!(with_ema(9_899, 10_000).get_price_if_close_to_ema(100), None);
+    assert!(with_ema(10_000, 10_000).get_price_if_close_to_ema(0).is_some());
+    assert!(with_ema(-10_100, -10_000).get_price_if_close_to_ema(100).is_some());
+    assert_eq!(with_ema(-10_101, -10_000).get_price_if_close_to_ema(100), None);
+    assert!(with_ema(i64::MIN, i64::MAX).get_price_if_close_to_ema(20_001).is_some());
+    assert_eq!(with_ema(i64::MIN, i64::MAX).get_price_if_close_to_ema(20_000), None);
+
+    assert_eq!(with_ema(0, 0).get_price_if_close_to_ema(u64::MAX), None);
+    let mut halted = with_ema(10_000, 10_000);
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_price_if_close_to_ema(100), None);
+  }
+
+  #[test]
+  fn test_get_validated_price() {
+    // 1.00 +- 0.01 (100 bps) published at slot 1000
+    let price = trading_price(100, 1, -2, 1000);
+    let expected = Ok(PriceConf { price: 100, conf: 1, expo: -2 });
+    assert_eq!(price.get_validated_price(1010, 10, 100), expected);
+    assert_eq!(price.get_validated_price(900, 0, 100), expected);
+
+    let mut halted = price;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_validated_price(1010, 10, 100), Err(PythError::PriceNotTrading));
+    assert_eq!(price.get_validated_price(1011, 10, 100), Err(PythError::StalePrice));
+    assert_eq!(price.get_validated_price(1010, 10, 99), Err(PythError::ConfidenceTooWide));
+    assert_eq!(trading_price(0, 0, -2, 1000).get_validated_price(1000, 0, u64::MAX), Err(PythError::ConfidenceTooWide));
+
+    // The checks are applied in order.
+    assert_eq!(halted.get_validated_price(u64::MAX, 0, 0), Err(PythError::PriceNotTrading));
+    assert_eq!(price.get_validated_price(u64::MAX, 0, 0), Err(PythError::StalePrice));
+  }
+
+  #[test]
+  fn test_get_current_price_with_corp_act_check() {
+    let price = trading_price(100, 1, -2, 1000);
+    assert_eq!(price.agg.corp_act, crate::CorpAction::NoCorpAct);
+    assert_eq!(price.get_current_price_with_corp_act_check(), Some(PriceConf { price: 100, conf: 1, expo: -2 }));
+
+    let mut halted = price;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_current_price_with_corp_act_check(), None);
+  }
+
+  #[test]
+  fn test_format_with_product() {
+    let product = product_with_attrs(&[("symbol", "Crypto.BTC/USD"), ("quote_currency", "USD")]);
+    let mut price = trading_price(5_700_012, 340, -2, 1000);
+    assert_eq!(price.display_exponent(), -2);
+    assert_eq!(price.format_with_product(&product), "BTC/USD: 57000.12 ± 3.40");
+
+    price.expo = -8;
+    assert_eq!(price.display_exponent(), -8);
+    assert_eq!(price.format_with_product(&product), "BTC/USD: 0.05700012 ± 0.00000340");
+
+    let product = product_with_attrs(&[("symbol", "Equity.US.AAPL/USD")]);
+    price.agg.status = PriceStatus::Halted;
+    assert_eq!(price.format_with_product(&product), "AAPL/USD: halted");
+
+    let product = product_with_attrs(&[("asset_type", "Crypto")]);
+    assert_eq!(price.format_with_product(&product), "unknown: halted");
+  }
+
+  #[test]
+  fn test_get_volatility_proxy_bps() {
+    let with_ema_conf = |ema: i64, ema_conf: i64| {
+      let mut price = with_ema(10_000, ema);
+      price.ema_confidence.val = ema_conf;
+      price
+    };
+
+    assert_eq!(with_ema_conf(10_000, 25).get_volatility_proxy_bps(), Some(25));
+    assert_eq!(with_ema_conf(10_000, 10_000).get_volatility_proxy_bps(), Some(10_000));
+    assert_eq!(with_ema_conf(3_000_000, 1_000).get_volatility_proxy_bps(), Some(3));
+    assert_eq!(with_ema_conf(3_000_000, 299).get_volatility_proxy_bps(), Some(0));
+    assert_eq!(with_ema_conf(-10_000, 25).get_volatility_proxy_bps(), Some(25));
+    assert_eq!(with_ema_conf(10_000, 0).get_volatility_proxy_bps(), Some(0));
+    // It does not depend on the aggregate status.
+    let mut halted = with_ema_conf(10_000, 25);
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_volatility_proxy_bps(), Some(25));
+
+    // Extremes
+    assert_eq!(with_ema_conf(i64::MIN, i64::MAX).get_volatility_proxy_bps(), Some(9_999));
+    assert_eq!(with_ema_conf(1, i64::MAX).get_volatility_proxy_bps(), None);
+    assert_eq!(with_ema_conf(1_000_000, i64::MAX).get_volatility_proxy_bps(), Some(92_233_720_368_547_758));
+    assert_eq!(with_ema_conf(0, 25).get_volatility_proxy_bps(), None);
+    assert_eq!(with_ema_conf(10_000, -25).get_volatility_proxy_bps(), None);
+  }
+
+  #[test]
+  fn test_ema_spot_ratio_bps() {
+    // Spot above the EMA
+    assert_eq!(with_ema(10_150, 10_000).ema_spot_ratio_bps(), Some(150));
+    assert_eq!(with_ema(20_000, 10_000).ema_spot_ratio_bps(), Some(10_000));
+    assert_eq!(with_ema(10_000, 10_000).ema_spot_ratio_bps(), Some(0));
+    // Spot below the EMA, rounded toward zero
+    assert_eq!(with_ema(9_850, 10_000).ema_spot_ratio_bps(), Some(-150));
+    assert_eq!(with_ema(29_999, 30_000).ema_spot_ratio_bps(), Some(0));
+    assert_eq!(with_ema(29_990, 30_000).ema_spot_ratio_bps(), Some(-3));
+    assert_eq!(with_ema(0, 10_000).ema_spot_ratio_bps(), Some(-10_000));
+    // The sign follows the direction of the move for negative prices too.
+    assert_eq!(with_ema(-9_850, -10_000).ema_spot_ratio_bps(), Some(150));
+    assert_eq!(with_ema(-10_150, -10_000).ema_spot_ratio_bps(), Some(-150));
+
+    // Extremes
+    assert_eq!(with_ema(i64::MAX, 1).ema_spot_ratio_bps(), None);
+    assert_eq!(with_ema(i64::MIN, i64::MAX).ema_spot_ratio_bps(), Some(-20_000));
+    assert_eq!(with_ema(i64::MAX, i64::MIN).ema_spot_ratio_bps(), Some(19_999));
+    assert_eq!(with_ema(10_000, 0).ema_spot_ratio_bps(), None);
+    let mut halted = with_ema(10_000, 10_000);
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.ema_spot_ratio_bps(), None);
+  }
+
+  #[test]
+  fn test_get_confidence_ratio_bps() {
+    assert_eq!(trading_price(10_000, 150, -2, 1000).get_confidence_ratio_bps(), Some(150));
+    assert_eq!(trading_price(-400, 1, -2, 1000).get_confidence_ratio_bps(), Some(25));
+
+    // A price account that has never been updated has a zero price.
+    assert_eq!(trading_price(0, 1, -2, 1000).get_confidence_ratio_bps(), None);
+    assert_eq!(Price::default().get_confidence_ratio_bps(), None);
+
+    let mut halted = trading_price(10_000, 150, -2, 1000);
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_confidence_ratio_bps(), None);
+  }
+
+  #[test]
+  fn test_iter_attributes() {
+    let product = product_with_attrs(&[
+      ("symbol", "Crypto.BTC/USD"),
+      ("asset_type", "Crypto"),
+      ("quote_currency", "USD"),
+      ("description", "BTC/USD"),
+    ]);
+
+    let attrs: Vec<(&str, &str)> = product.iter_attributes().collect();
+    assert_eq!(attrs, vec![
+      ("symbol", "Crypto.BTC/USD"),
+      ("asset_type", "Crypto"),
+      ("quote_currency", "USD"),
+      ("description", "BTC/USD"),
+    ]);
+
+    assert_eq!(product.get_attribute("symbol"), Some("Crypto.BTC/USD"));
+    assert_eq!(product.get_attribute("asset_type"), Some("Crypto"));
+    assert_eq!(product.get_attribute("quote_currency"), Some("USD"));
+    assert_eq!(product.get_attribute("base"), None);
+    assert_eq!(product.get_attribute(""), None);
+  }
+
+  #[test]
+  fn test_well_known_attributes() {
+    // The attributes of the BTC/USD product on mainnet.
+    let product = product_with_attrs(&[
+      ("asset_type", "Crypto"),
+      ("base", "BTC"),
+      ("description", "BTC/USD"),
+      ("generic_symbol", "BTCUSD"),
+      ("quote_currency", "USD"),
+      ("symbol", "Crypto.BTC/USD"),
+    ]);
+    assert_eq!(product.symbol(), Some("Crypto.BTC/USD"));
+    assert_eq!(product.asset_type(), Some("Crypto"));
+    assert_eq!(product.quote_currency(), Some("USD"));
+    assert_eq!(product.base(), Some("BTC"));
+
+    let product = product_with_attrs(&[("symbol", "Equity.US.AAPL/USD"), ("asset_type", "Equity")]);
+    assert_eq!(product.symbol(), Some("Equity.US.AAPL/USD"));
+    assert_eq!(product.asset_type(), Some("Equity"));
+    assert_eq!(product.quote_currency(), None);
+    assert_eq!(product.base(), None);
+  }
+
+  #[test]
+  fn test_debug() {
+    let price = format!("{:?}", Price::zeroed());
+    assert!(price.starts_with("Price { magic: 0, ver: 0, atype: 0, size: 0, ptype: Unknown, expo: 0,"));
+
+    let product = product_with_attrs(&[("symbol", "Crypto.BTC/USD"), ("asset_type", "Crypto")]);
+    assert_eq!(
+      format!("{:?}", product),
+      format!(
+        "Product {{ magic: 0, ver: 0, atype: 0, size: 0, px_acc: {:?}, \
+         attr: {{\"symbol\": \"Crypto.BTC/USD\", \"asset_type\": \"Crypto\"}} }}",
+        AccKey::default()
+      )
+    );
+
+    let mut mapping = Mapping::zeroed();
+    mapping.num = 2;
+    mapping.products[0] = AccKey { val: [1; 32] };
+    mapping.products[1] = AccKey { val: [2; 32] };
+    let mapping = format!("{:?}", mapping);
+    assert!(mapping.ends_with("products: [2 products] }"));
+    assert!(mapping.len() < 400);
+  }
+
+  #[test]
+  fn test_iter_attributes_malformed() {
+    // Value length runs past the end of the buffer.
+    let long_key = "k".repeat(190);
+    let long_val = "v".repeat(255);
+    let mut product = product_with_attrs(&[("symbol", &long_val), (&long_key, "")]);
+    let last = 1 + "symbol".len() + 1 + long_val.len() + 1 + long_key.len();
+    assert!(last < PROD_ATTR_SIZE);
+    product.attr[last] = (PROD_ATTR_SIZE - last) as u8;
+    assert_eq!(product.iter_attributes().count(), 1);
+    assert_eq!(product.get_attribute(&long_key), None);
+
+    // Invalid UTF-8 terminates iteration.
+    let mut product = product_with_attrs(&[("symbol", "BTC"), ("asset_type", "Crypto")]);
+    product.attr[8] = 0xff;
+    assert_eq!(product.iter_attributes().count(), 0);
+    assert_eq!(product.get_attribute("asset_type"), None);
+  }
+
+  #[test]
+  fn test_iter_products() {
+    let key = |b: u8| AccKey { val: [b; 32] };
+    let mut mapping = Mapping::zeroed();
+    mapping.products[0] = key(1);
+    mapping.products[1] = key(2);
+    mapping.products[3] = key(4);
+    // Entries past `num` are ignored.
+    mapping.products[4] = key(5);
+    mapping.num = 4;
+
+    let products: Vec<&AccKey> = mapping.iter_products().collect();
+    assert_eq!(products, vec![&key(1), &key(2), &key(4)]);
+
+    mapping.num = 0;
+    assert_eq!(mapping.iter_products().count(), 0);
+
+    // A corrupt `num` is clamped to the size of the table.
+    mapping.num = u32::MAX;
+    mapping.products[MAP_TABLE_SIZE - 1] = key(6);
+    assert_eq!(mapping.iter_products().count(), 5);
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_products_across_chain() {
+    let key = |b: u8| AccKey { val: [b; 32] };
+    let mapping_data = |products: &[AccKey], next: AccKey| {
+      let mut mapping = Mapping::zeroed();
+      mapping.magic = MAGIC;
+      mapping.ver = VERSION_2;
+      mapping.atype = AccountType::Mapping as u32;
+      mapping.size = size_of::<Mapping>() as u32;
+      mapping.num = products.len() as u32;
+      mapping.products[..products.len()].copy_from_slice(products);
+      mapping.next = next;
+      mapping
+    };
+
+    let first = mapping_data(&[key(1), key(2)], key(10));
+    let second = mapping_data(&[key(3)], AccKey::default());
+    let mut fetched = Vec::new();
+    let products = crate::products_across_chain(&first, |k| {
+      fetched.push(k);
+      if k == Pubkey::new_from_array([10; 32]) {
+        Some(bytemuck::bytes_of(&second).to_vec())
+      } else {
+        None
+      }
+    });
+    let expected: Vec<Pubkey> = [1, 2, 3].iter().map(|&b| Pubkey::new_from_array([b; 32])).collect();
+    assert_eq!(products, Ok(expected));
+    assert_eq!(fetched, vec![Pubkey::new_from_array([10; 32])]);
+
+    // A single mapping account does not fetch anything.
+    let products = crate::products_across_chain(&second, |_| panic!("unexpected fetch"));
+    assert_eq!(products, Ok(vec![Pubkey::new_from_array([3; 32])]));
+
+    // Missing accounts and cycles are errors.
+    assert_eq!(crate::products_across_chain(&first, |_| None), Err(PythError::InvalidAccountData));
+    let cycle = mapping_data(&[key(3)], key(10));
+    let fetch = |_| Some(bytemuck::bytes_of(&cycle).to_vec());
+    assert_eq!(crate::products_across_chain(&first, fetch), Err(PythError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_component_price_spread() {
+    let mut price = trading_price(10_000, 5, -2, 1000);
+    price.comp[..5].copy_from_slice(&[
+      comp(10_010, 3, PriceStatus::Trading),
+      comp(9_980, 4, PriceStatus::Trading),
+      comp(20_000, 1, PriceStatus::Halted),
+      comp(10_050, 6, PriceStatus::Trading),
+      comp(10_000, 2, PriceStatus::Trading),
+    ]);
+
+    price.num = 5;
+    // 100.50 - 99.80, ignoring the halted component
+    assert_eq!(price.component_price_spread(), Some(PriceConf { price: 70, conf: 10, expo: -2 }));
+    // Components past `num` are ignored.
+    price.num = 2;
+    assert_eq!(price.component_price_spread(), Some(PriceConf { price: 30, conf: 7, expo: -2 }));
+
+    // Fewer than two trading components
+    price.num = 1;
+    assert_eq!(price.component_price_spread(), None);
+    price.comp[1].agg.status = PriceStatus::Unknown;
+    price.num = 3;
+    assert_eq!(price.component_price_spread(), None);
+
+    // Equal prices have no spread.
+    price.comp[1] = comp(10_010, 1, PriceStatus::Trading);
+    assert_eq!(price.component_price_spread(), Some(PriceConf { price: 0, conf: 4, expo: -2 }));
+
+    price.comp[0] = comp(i64::MIN, 0, PriceStatus::Trading);
+    assert_eq!(price.component_price_spread(), None);
+  }
+
+  #[test]
+  fn test_num_components_and_quoters() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.num = 12;
+    price.num_qt = 5;
+    assert_eq!(price.num_components(), 12);
+    assert_eq!(price.num_quoters_in_aggregate(), 5);
+    assert_eq!(price.get_publisher_count(), 5);
+    // Quorum checks count the quoters, not the components.
+    assert!(price.get_price_with_min_publishers(5).is_some());
+    assert!(price.get_price_with_min_publishers(6).is_none());
+  }
+
+  #[test]
+  fn test_get_component() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    for (i, comp) in price.comp.iter_mut().enumerate() {
+      comp.publisher = AccKey { val: [i as u8 + 1; 32] };
+    }
+    assert!(price.get_component(0).is_none());
+
+    price.num = 3;
+    assert_eq!(price.get_component(0), Some(&price.comp[0]));
+    assert_eq!(price.get_component(2).map(|comp| comp.publisher), Some(AccKey { val: [3; 32] }));
+    assert_eq!(price.get_component(3), None);
+    assert_eq!(price.get_component(40), None);
+    assert_eq!(price.get_component(usize::MAX), None);
+
+    // `num` is never trusted beyond the size of the array.
+    price.num = 40;
+    assert_eq!(price.get_component(31).map(|comp| comp.publisher), Some(AccKey { val: [32; 32] }));
+    assert_eq!(price.get_component(32), None);
+    assert_eq!(price.get_component(39), None);
+  }
+
+  #[test]
+  fn test_first_price_key() {
+    let mut product = product_with_attrs(&[("symbol", "Crypto.BTC/USD")]);
+    assert_eq!(product.first_price_key(), None);
+
+    product.px_acc = AccKey { val: [7; 32] };
+    assert_eq!(product.first_price_key(), Some(Pubkey::new_from_array([7; 32])));
+    let mut key = [0; 32];
+    key[31] = 1;
+    product.px_acc = AccKey { val: key };
+    assert_eq!(product.first_price_key(), Some(Pubkey::new_from_array(key)));
+  }
+
+  #[test]
+  fn test_next_price_key() {
+    let mut price = Price::default();
+    assert_eq!(price.next_price_key(), None);
+
+    price.next = AccKey { val: [7; 32] };
+    assert_eq!(price.next_price_key(), Some(Pubkey::new_from_array([7; 32])));
+  }
+
+  #[test]
+  fn test_get_current_price_unchecked() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    assert_eq!(price.get_status(), PriceStatus::Trading);
+    assert_eq!(price.get_current_price_unchecked(), PriceConf { price: 100, conf: 1, expo: -2 });
+
+    for status in [PriceStatus::Unknown, PriceStatus::Halted, PriceStatus::Auction] {
+      price.agg.status = status;
+      assert_eq!(price.get_status(), status);
+      assert_eq!(price.get_current_price(), None);
+      assert_eq!(price.get_current_price_unchecked(), PriceConf { price: 100, conf: 1, expo: -2 });
+    }
+  }
+
+  #[test]
+  fn test_get_prev_price() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.prev_price = 95;
+    price.prev_conf = 3;
+    price.prev_slot = 990;
+
+    assert_eq!(price.get_prev_price(), PriceConf { price: 95, conf: 3, expo: -2 });
+    assert_eq!(price.get_prev_slot(), 990);
+    assert_eq!(price.get_current_price(), Some(PriceConf { price: 100, conf: 1, expo: -2 }));
+  }
+
+  #[test]
+  fn test_lp_token_price() {
+    // A pool of 1000 SOL at 20.00 +- 0.10 and 20000 USDC at 1.00 +- 0.01, worth 40000 +- 300
+    let sol = trading_price(2_000, 10, -2, 1000);
+    let usdc = trading_price(100, 1, -2, 1000);
+    let reserves = [(sol, 1_000, 0), (usdc, 20_000, 0)];
+
+    // 4000 LP tokens: 10.00 +- 0.075 each, with the confidence rounded up
+    assert_eq!(Price::lp_token_price(&reserves, 4_000, 0, -2), Some(PriceConf { price: 1_000, conf: 8, expo: -2 }));
+    assert_eq!(Price::lp_token_price(&reserves, 4_000, 0, -4), Some(PriceConf { price: 100_000, conf: 750, expo: -4 }));
+    // The same supply with 6 decimals
+    assert_eq!(
+      Price::lp_token_price(&reserves, 4_000_000_000, -6, -2),
+      Some(PriceConf { price: 1_000, conf: 8, expo: -2 })
+    );
+    // 3 LP tokens: 13333.33 +- 100 each
+    assert_eq!(
+      Price::lp_token_price(&reserves, 3, 0, -2),
+      Some(PriceConf { price: 1_333_333, conf: 10_000, expo: -2 })
+    );
+    // 1.5 LP tokens: 26666.67 +- 200 each
+    assert_eq!(
+      Price::lp_token_price(&reserves, 15, -1, -2),
+      Some(PriceConf { price: 2_666_667, conf: 20_000, expo: -2 })
+    );
+
+    assert_eq!(Price::lp_token_price(&reserves, 0, 0, -2), None);
+    assert_eq!(Price::lp_token_price(&reserves, -4_000, 0, -2), None);
+    assert_eq!(Price::lp_token_price(&[], 4_000, 0, -2), None);
+    let mut halted = sol;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(Price::lp_token_price(&[(halted, 1_000, 0), (usdc, 20_000, 0)], 4_000, 0, -2), None);
+  }
+
+  #[test]
+  fn test_price_basket() {
+    use crate::{BasketError, BasketOperation};
+
+    let usdc = trading_price(100_000_000, 10_000, -8, 1000);
+    let sol = trading_price(4_000, 2, -2, 1000);
+
+    // 5 USDC + 0.25 SOL
+    let basket = [(usdc, 5, 0), (sol, 25, -2)];
+    assert_eq!(Price::price_basket(&basket, -2), Some(PriceConf { price: 1_500, conf: 2, expo: -2 }));
+    assert_eq!(Price::price_basket_checked(&basket, -2), Ok(PriceConf { price: 1_500, conf: 2, expo: -2 }));
+
+    let failed = |index, operation| Err(BasketError::ArithmeticFailed { index, operation });
+    assert_eq!(Price::price_basket_checked(&[], -2), Err(BasketError::EmptyBasket));
+    assert_eq!(Price::price_basket(&[], -2), None);
+
+    let mut halted = sol;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(
+      Price::price_basket_checked(&[usdc, sol, halted].map(|p| (p, 1, 0)), -2),
+      Err(BasketError::PriceNotTrading { index: 2 })
+    );
+
+    // The exponent of the product overflows.
+    let basket = [(usdc, 1, 0), (sol, 1, i32::MIN)];
+    assert_eq!(Price::price_basket_checked(&basket, -2), failed(1, BasketOperation::Mul));
+    // The value of the second entry does not fit into an i64 with the result exponent.
+    let large = trading_price(i64::MAX, 0, 0, 1000);
+    let basket = [(usdc, 1, 0), (large, 1, 0)];
+    assert_eq!(Price::price_basket_checked(&basket, -2), failed(1, BasketOperation::Scale));
+    // Each entry fits, but the sum does not.
+    let basket = [(large, 1, 0), (large, 1, 0)];
+    assert_eq!(Price::price_basket_checked(&basket, 0), failed(1, BasketOperation::Add));
+    assert_eq!(Price::price_basket(&[(large, 1, 0), (large, 1, 0)], 0), None);
+  }
+
+  #[test]
+  fn test_get_price_bounds() {
+    let price = trading_price(100, 3, -2, 1000);
+    let bound = |price| PriceConf { price, conf: 0, expo: -2 };
+    assert_eq!(price.get_price_bounds(), Some((bound(97), bound(103))));
+
+    let mut halted = price;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_price_bounds(), None);
+    assert_eq!(trading_price(i64::MAX, 1, -2, 1000).get_price_bounds(), None);
+  }
+
+  #[test]
+  fn test_get_position_value() {
+    let sol = trading_price(4_000, 2, -2, 1000);
+
+    // Long and short 0.25 SOL
+    assert_eq!(sol.get_position_value(25, -2, -2), Some(PriceConf { price: 1_000, conf: 1, expo: -2 }));
+    assert_eq!(sol.get_position_value(-25, -2, -2), Some(PriceConf { price: -1_000, conf: 1, expo: -2 }));
+    assert_eq!(sol.get_position_value(3, 6, 4), Some(PriceConf { price: 12_000, conf: 6, expo: 4 }));
+    assert_eq!(sol.get_position_value(0, 0, -2), Some(PriceConf { price: 0, conf: 0, expo: -2 }));
+
+    let mut halted = sol;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_position_value(25, -2, -2), None);
+    // The exponent of the product overflows, or the value does not fit the result exponent.
+    assert_eq!(sol.get_position_value(1, i32::MIN, -2), None);
+    assert_eq!(sol.get_position_value(i64::MAX, 0, -8), None);
+  }
+
+  #[test]
+  fn test_get_price_in_quote_result() {
+    let base = trading_price(100, 0, -2, 1000);
+    let quote = trading_price(200, 0, -2, 1000);
+    let expected = PriceConf { price: 50, conf: 0, expo: -2 };
+    assert_eq!(base.get_price_in_quote_result(&quote, -2), Ok(expected));
+    assert_eq!(base.get_price_in_quote(&quote, -2), Some(expected));
+
+    let mut halted = base;
+    halted.agg.status = PriceStatus::Halted;
+    assert_eq!(halted.get_price_in_quote_result(&quote, -2), Err(PythError::BasePriceNotTrading));
+    assert_eq!(base.get_price_in_quote_result(&halted, -2), Err(PythError::QuotePriceNotTrading));
+    // The base price is checked first.
+    assert_eq!(halted.get_price_in_quote_result(&halted, -2), Err(PythError::BasePriceNotTrading));
+
+    let zero = trading_price(0, 0, -2, 1000);
+    assert_eq!(base.get_price_in_quote_result(&zero, -2), Err(PythError::ArithmeticFailed));
+    // The result does not fit with the requested exponent.
+    assert_eq!(base.get_price_in_quote_result(&quote, -30), Err(PythError::ArithmeticFailed));
+    assert_eq!(base.get_price_in_quote(&zero, -2), None);
+
+    // 1 / 3 is rounded once at the requested exponent, rather than after `div` has already
+    // rounded it to 9 digits.
+    let one = trading_price(1, 0, 0, 1000);
+    let three = trading_price(3, 0, 0, 1000);
+    assert_eq!(one.get_price_in_quote(&three, -18), Some(PriceConf { price: 333_333_333_333_333_333, conf: 0, expo: -18 }));
+    assert_eq!(
+      one.get_current_price().unwrap().div(&three.get_current_price().unwrap()).unwrap().scale_to_exponent(-18),
+      Some(PriceConf { price: 333_333_333_000_000_000, conf: 0, expo: -18 })
+    );
+  }
+
+  #[test]
+  fn test_to_owned_feed() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.prod = AccKey { val: [7; 32] };
+    price.ema_price.val = 98;
+    price.ema_confidence.val = 4;
+    price.prev_price = 95;
+    price.prev_conf = 3;
+    price.prev_slot = 990;
+
+    let feed = price.to_owned_feed();
+    assert_eq!(feed.prod, price.prod);
+    assert_eq!(feed.agg, price.agg);
+    assert_eq!(feed.get_current_price(), price.get_current_price());
+    assert_eq!(feed.get_ema_price(), price.get_ema_price());
+    assert_eq!(feed.get_prev_price(), price.get_prev_price());
+    assert_eq!(feed.prev_slot, 990);
+
+    price.agg.status = PriceStatus::Halted;
+    assert_eq!(price.to_owned_feed().get_current_price(), None);
+  }
+
+  #[test]
+  fn test_snapshot() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.ema_price.val = 98;
+    price.ema_confidence.val = 4;
+    price.num_qt = 5;
+
+    let snapshot = price.snapshot();
+    assert_eq!(snapshot.price, PriceConf { price: 100, conf: 1, expo: -2 });
+    assert_eq!(snapshot.ema, PriceConf { price: 98, conf: 4, expo: -2 });
+    assert_eq!(Some(snapshot.ema), price.get_ema_price());
+    assert_eq!(snapshot.publish_slot, 1000);
+    assert_eq!(snapshot.num_publishers, 5);
+    assert_eq!(snapshot.status, PriceStatus::Trading);
+
+    price.agg.status = PriceStatus::Halted;
+    assert_eq!(price.snapshot().status, PriceStatus::Halted);
+    assert_eq!(price.snapshot().price, price.get_current_price_unchecked());
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_load_price_feed() {
+    let mut price = valid_price_account(100);
+    let data = bytemuck::bytes_of(&price).to_vec();
+
+    let feed = crate::load_price_feed(&data).unwrap();
+    // The feed outlives the account data.
+    drop(data);
+    assert_eq!(feed, price.to_owned_feed());
+
+    price.atype = AccountType::Product as u32;
+    assert_eq!(crate::load_price_feed(bytemuck::bytes_of(&price)), Err(PythError::WrongAccountType));
+    assert_eq!(crate::load_price_feed(&[0; 10]), Err(PythError::InvalidAccountData));
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_load_price_account_info() {
+    use solana_program::account_info::AccountInfo;
+
+    let price = valid_price_account(100);
+    let mut data = bytemuck::bytes_of(&price).to_vec();
+    let key = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+    let mut lamports = 0;
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &oracle, false, 0);
+
+    let loaded = crate::load_price_account_info(&account, &oracle).unwrap();
+    assert_eq!(loaded.get_current_price(), price.get_current_price());
+    drop(loaded);
+
+    let attacker = Pubkey::new_unique();
+    assert_eq!(crate::load_price_account_info(&account, &attacker).err(), Some(PythError::WrongOwner));
+
+    // The account data is still validated.
+    account.try_borrow_mut_data().unwrap()[8] = AccountType::Product as u8;
+    assert_eq!(crate::load_price_account_info(&account, &oracle).err(), Some(PythError::WrongAccountType));
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_load_price_account_info_owned() {
+    use solana_program::account_info::AccountInfo;
+
+    let price = valid_price_account(100);
+    let mut data = bytemuck::bytes_of(&price).to_vec();
+    let key = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+    let mut lamports = 0;
+    let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &oracle, false, 0);
+
+    let loaded = crate::load_price_account_info_owned(&account, &oracle).unwrap();
+    // The account data is not borrowed anymore, so it can be borrowed mutably.
+    account.try_borrow_mut_data().unwrap()[8] = AccountType::Product as u8;
+    assert_eq!(loaded, price);
+
+    assert_eq!(crate::load_price_account_info_owned(&account, &oracle).err(), Some(PythError::WrongAccountType));
+    let attacker = Pubkey::new_unique();
+    assert_eq!(crate::load_price_account_info_owned(&account, &attacker).err(), Some(PythError::WrongOwner));
+
+    // The account data is already mutably borrowed.
+    account.try_borrow_mut_data().unwrap()[8] = AccountType::Price as u8;
+    let borrow = account.try_borrow_mut_data().unwrap();
+    assert_eq!(crate::load_price_account_info_owned(&account, &oracle).err(), Some(PythError::InvalidAccountData));
+    drop(borrow);
+    assert_eq!(crate::load_price_account_info_owned(&account, &oracle), Ok(price));
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_load_price_owned() {
+    fn load(price: &Price) -> crate::OwnedPrice {
+      let data = bytemuck::bytes_of(price).to_vec();
+      crate::load_price_owned(data).unwrap()
+    }
+
+    let price = valid_price_account(100);
+
+    // The handle outlives the function that created it, and can be moved around.
+    let owned = load(&price);
+    let moved = vec![owned];
+    let owned = moved.into_iter().next().unwrap();
+    assert_eq!(*owned, price);
+    assert_eq!(owned.get_current_price(), Some(PriceConf { price: 100, conf: 1, expo: -2 }));
+    assert_eq!(owned.into_data(), bytemuck::bytes_of(&price));
+
+    let data = account_data(size_of::<Price>() - 1, VERSION_2, AccountType::Price);
+    assert_eq!(
+      crate::load_price_owned(data),
+      Err(PythError::AccountTooSmall { expected: size_of::<Price>(), actual: size_of::<Price>() - 1 })
+    );
+    let data = account_data(size_of::<Price>(), VERSION_2, AccountType::Product);
+    assert_eq!(crate::load_price_owned(data), Err(PythError::WrongAccountType));
+  }
+
+  #[cfg(all(feature = "base64", target_endian = "little"))]
+  #[test]
+  fn test_load_price_base64() {
+    use base64::Engine;
+
+    let price = valid_price_account(100);
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytemuck::bytes_of(&price));
+    assert_eq!(*crate::load_price_base64(&encoded).unwrap(), price);
+
+    // The header of a price account, without the rest of the account.
+    assert_eq!(
+      crate::load_price_base64("1MOyoQIAAAADAAAA8AwAAA=="),
+      Err(PythError::AccountTooSmall { expected: size_of::<Price>(), actual: 16 })
+    );
+    assert_eq!(crate::load_price_base64("1MOyoQIAAAADAAAA8AwAAA="), Err(PythError::InvalidBase64));
+    assert_eq!(crate::load_price_base64("not base64!"), Err(PythError::InvalidBase64));
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_price_accounts() {
+    use std::collections::HashMap;
+
+    let price_account = |price: i64, next: AccKey| {
+      let mut account = valid_price_account(price);
+      account.next = next;
+      account
+    };
+    let mut accounts = HashMap::new();
+    accounts.insert(Pubkey::new_from_array([1; 32]), price_account(100, AccKey { val: [2; 32] }));
+    accounts.insert(Pubkey::new_from_array([2; 32]), price_account(200, AccKey::default()));
+    let fetch = |key: Pubkey| accounts.get(&key).map(|price| bytemuck::bytes_of(price).to_vec());
+
+    let mut product = Product::zeroed();
+    product.px_acc = AccKey { val: [1; 32] };
+    let prices = crate::price_accounts(&product, fetch).unwrap();
+    let values: Vec<i64> = prices.iter().map(|price| price.agg.price).collect();
+    assert_eq!(values, vec![100, 200]);
+
+    // A product without price accounts.
+    assert_eq!(crate::price_accounts(&Product::zeroed(), fetch), Ok(vec![]));
+
+    // Missing accounts, cycles and invalid accounts are errors.
+    product.px_acc = AccKey { val: [3; 32] };
+    assert_eq!(crate::price_accounts(&product, fetch), Err(PythError::InvalidAccountData));
+    accounts.insert(Pubkey::new_from_array([3; 32]), price_account(300, AccKey { val: [3; 32] }));
+    let fetch = |key: Pubkey| accounts.get(&key).map(|price| bytemuck::bytes_of(price).to_vec());
+    assert_eq!(crate::price_accounts(&product, fetch), Err(PythError::InvalidAccountData));
+    assert_eq!(crate::price_accounts(&product, |_| Some(vec![0; 8])), Err(PythError::InvalidAccountData));
+  }
+
+  /// Account data of `size` bytes with only the header fields set. All bytes are in use.
+  fn account_data(size: usize, ver: u32, atype: AccountType) -> Vec<u8> {
+    let mut data = vec![0u8; size];
+    data[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&ver.to_le_bytes());
+    data[8..12].copy_from_slice(&(atype as u32).to_le_bytes());
+    data[12..16].copy_from_slice(&(size as u32).to_le_bytes());
+    data
+  }
+
+  #[test]
+  fn test_load_versions() {
+    let unsupported = |found| Err(PythError::UnsupportedVersion { found, max_supported: VERSION_2 });
+
+    let data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    assert!(crate::load_price(&data).is_ok());
+    let data = account_data(size_of::<Product>(), VERSION_2, AccountType::Product);
+    assert!(crate::load_product(&data).is_ok());
+    let data = account_data(size_of::<Mapping>(), VERSION_2, AccountType::Mapping);
+    assert!(crate::load_mapping(&data).is_ok());
+
+    let data = account_data(size_of::<Price>(), 1, AccountType::Price);
+    assert_eq!(crate::load_price(&data).map(|_| ()), unsupported(1));
+    let data = account_data(size_of::<Product>(), 1, AccountType::Product);
+    assert_eq!(crate::load_product(&data).map(|_| ()), unsupported(1));
+    let data = account_data(size_of::<Mapping>(), 1, AccountType::Mapping);
+    assert_eq!(crate::load_mapping(&data).map(|_| ()), unsupported(1));
+
+    // A future version is recognized from the header even if its layout is different.
+    let data = account_data(size_of::<Price>(), 3, AccountType::Price);
+    assert_eq!(crate::load_price(&data).map(|_| ()), unsupported(3));
+    let data = account_data(16, 3, AccountType::Price);
+    assert_eq!(crate::load_price(&data).map(|_| ()), unsupported(3));
+
+    let mut data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    data[0] ^= 1;
+    assert_eq!(crate::load_price(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+    assert_eq!(crate::load_price(&[]).map(|_| ()), Err(PythError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_iter_trading_components() {
+    let mut price = Price::default();
+    price.comp[0] = comp(10, 0, PriceStatus::Trading);
+    price.comp[1] = comp(11, 0, PriceStatus::Halted);
+    price.comp[2] = comp(12, 0, PriceStatus::Trading);
+    price.comp[3] = comp(13, 0, PriceStatus::Unknown);
+    price.comp[4] = comp(14, 0, PriceStatus::Auction);
+    price.comp[5] = comp(15, 0, PriceStatus::Trading);
+    // Components past `num` are ignored.
+    price.comp[6] = comp(16, 0, PriceStatus::Trading);
+    price.num = 6;
+
+    let prices: Vec<i64> = price.iter_trading_components().map(|comp| comp.agg.price).collect();
+    assert_eq!(prices, vec![10, 12, 15]);
+
+    price.num = 0;
+    assert_eq!(price.iter_trading_components().count(), 0);
+
+    // A corrupt `num` is clamped to the number of components.
+    price.num = u32::MAX;
+    price.comp[31] = comp(41, 0, PriceStatus::Trading);
+    assert_eq!(price.iter_trading_components().count(), 5);
+  }
+
+  #[test]
+  fn test_component_price_conf() {
+    let info = |price: i64, conf: u64, status: PriceStatus| PriceInfo { price, conf, status, ..Default::default() };
+    let mut price = Price { expo: -2, num: 3, ..Default::default() };
+    price.comp[0] = PriceComp {
+      agg: info(100, 1, PriceStatus::Trading),
+      latest: info(101, 2, PriceStatus::Trading),
+      ..Default::default()
+    };
+    price.comp[1] = PriceComp {
+      agg: info(200, 1, PriceStatus::Trading),
+      latest: info(0, 0, PriceStatus::Halted),
+      ..Default::default()
+    };
+    price.comp[2] = PriceComp {
+      agg: info(-300, 3, PriceStatus::Unknown),
+      latest: info(-301, 3, PriceStatus::Trading),
+      ..Default::default()
+    };
+    price.comp[3] = price.comp[0];
+
+    assert_eq!(price.component_price_conf(0), Some(PriceConf { price: 100, conf: 1, expo: -2 }));
+    assert_eq!(price.component_latest_conf(0), Some(PriceConf { price: 101, conf: 2, expo: -2 }));
+    assert_eq!(price.component_price_conf(1), Some(PriceConf { price: 200, conf: 1, expo: -2 }));
+    assert_eq!(price.component_latest_conf(1), None);
+    assert_eq!(price.component_price_conf(2), None);
+    assert_eq!(price.component_latest_conf(2), Some(PriceConf { price: -301, conf: 3, expo: -2 }));
+    // Components past `num` are out of range.
+    assert_eq!(price.component_price_conf(3), None);
+    assert_eq!(price.component_latest_conf(32), None);
+    assert_eq!(price.component_price_conf(usize::MAX), None);
+  }
+
+  #[test]
+  fn test_compute_aggregate() {
+    let mut price = Price { expo: -2, num: 1, ..Default::default() };
+    assert_eq!(price.compute_aggregate(), None);
+
+    // Votes: 90, 100, 110
+    price.comp[0] = comp(100, 10, PriceStatus::Trading);
+    assert_eq!(price.compute_aggregate(), Some(PriceConf { price: 100, conf: 10, expo: -2 }));
+
+    // Votes: 90, 100, 110, 116, 120, 124
+    price.comp[1] = comp(120, 4, PriceStatus::Trading);
+    price.num = 2;
+    assert_eq!(price.compute_aggregate(), Some(PriceConf { price: 113, conf: 13, expo: -2 }));
+
+    // Votes: 100 x3, 101 x3, 200 x3. Components that are not trading are ignored.
+    price.comp[0] = comp(100, 0, PriceStatus::Trading);
+    price.comp[1] = comp(500, 0, PriceStatus::Halted);
+    price.comp[2] = comp(101, 0, PriceStatus::Trading);
+    price.comp[3] = comp(200, 0, PriceStatus::Trading);
+    price.num = 4;
+    assert_eq!(price.compute_aggregate(), Some(PriceConf { price: 101, conf: 99, expo: -2 }));
+
+    // Votes beyond the range of i64 do not overflow.
+    price.comp[0] = comp(i64::MIN, u64::MAX, PriceStatus::Trading);
+    price.num = 1;
+    assert_eq!(price.compute_aggregate(), Some(PriceConf { price: i64::MIN, conf: u64::MAX, expo: -2 }));
+  }
+
+  #[test]
+  fn test_get_publisher_price() {
+    let mut price = Price::default();
+    for (i, comp) in price.comp.iter_mut().enumerate() {
+      comp.publisher = AccKey { val: [i as u8 + 1; 32] };
+      comp.agg.price = 100 + i as i64;
+    }
+    price.num = 5;
+    price.num_qt = 3;
+
+    let publisher = Pubkey::new_from_array([3; 32]);
+    assert_eq!(price.get_publisher_price(&publisher).map(|comp| comp.agg.price), Some(102));
+    assert_eq!(price.get_publisher_price(&Pubkey::new_unique()), None);
+    // Components past `num` are ignored.
+    assert_eq!(price.get_publisher_price(&Pubkey::new_from_array([6; 32])), None);
+    assert_eq!(price.get_publisher_count(), 3);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde_round_trip() {
+    let product = product_with_attrs(&[("symbol", "Crypto.BTC/USD")]);
+    let json = serde_json::to_string(&product).unwrap();
+    assert_eq!(serde_json::from_str::<Product>(&json).unwrap(), product);
+
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.comp[31].agg.price = 42;
+    let json = serde_json::to_string(&price).unwrap();
+    assert_eq!(serde_json::from_str::<Price>(&json).unwrap(), price);
+
+    let mut mapping = Mapping::zeroed();
+    mapping.products[MAP_TABLE_SIZE - 1] = AccKey { val: [5; 32] };
+    let json = serde_json::to_string(&mapping).unwrap();
+    assert_eq!(serde_json::from_str::<Mapping>(&json).unwrap(), mapping);
+  }
+
+  #[cfg(feature = "price-borsh")]
+  #[test]
+  fn test_borsh_round_trip() {
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.comp[31].latest.price = 42;
+    price.prod = AccKey { val: [7; 32] };
+    let data = price.try_to_vec().unwrap();
+    assert_eq!(Price::try_from_slice(&data).unwrap(), price);
+    assert!(Price::try_from_slice(&data[..data.len() - 1]).is_err());
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_load_misaligned() {
+    let data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    // Copy the account to an offset that is not a multiple of the alignment of `Price`.
+    let mut buffer = vec![0u8; data.len() + 1];
+    let offset = if buffer.as_ptr().align_offset(core::mem::align_of::<Price>()) == 0 { 1 } else { 0 };
+    buffer[offset..offset + data.len()].copy_from_slice(&data);
+    let misaligned = &buffer[offset..offset + data.len()];
+
+    assert_eq!(crate::load_price(misaligned).map(|_| ()), Err(PythError::MisalignedAccountData));
+    assert!(crate::load_price(&data).is_ok());
+  }
+
+  #[test]
+  fn test_load_undersized() {
+    let data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    let undersized = &data[..size_of::<Price>() - 1];
+    assert_eq!(
+      crate::load_price(undersized).map(|_| ()),
+      Err(PythError::AccountTooSmall { expected: size_of::<Price>(), actual: size_of::<Price>() - 1 })
+    );
+    let data = account_data(size_of::<Product>(), VERSION_2, AccountType::Product);
+    assert_eq!(
+      crate::load_product(&data[..100]).map(|_| ()),
+      Err(PythError::AccountTooSmall { expected: size_of::<Product>(), actual: 100 })
+    );
+  }
+
+  #[test]
+  fn test_load_price_for_product() {
+    let product = Pubkey::new_from_array([7; 32]);
+    let mut data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    // prod
+    data[112..144].copy_from_slice(&[7; 32]);
+
+    let price = crate::load_price_for_product(&data, &product).unwrap();
+    assert_eq!(price.prod, AccKey { val: [7; 32] });
+    assert_eq!(
+      crate::load_price_for_product(&data, &Pubkey::new_from_array([8; 32])).map(|_| ()),
+      Err(PythError::ProductMismatch)
+    );
+    // The account is validated before the product is checked.
+    assert_eq!(
+      crate::load_price_for_product(&data[..100], &product).map(|_| ()),
+      Err(PythError::AccountTooSmall { expected: size_of::<Price>(), actual: 100 })
+    );
+  }
+
+  #[test]
+  fn test_load_trailing_data() {
+    let with_trailing_zeros = |data: &[u8]| {
+      let mut data = data.to_vec();
+      data.extend_from_slice(&[0; 100]);
+      data
+    };
+
+    let mut data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    data[208..216].copy_from_slice(&12345i64.to_le_bytes());
+    data[size_of::<Price>() - 8..].copy_from_slice(&42i64.to_le_bytes());
+    let padded = with_trailing_zeros(&data);
+    assert_eq!(padded.len(), size_of::<Price>() + 100);
+    let price = crate::load_price(&padded).unwrap();
+    assert_eq!(price, crate::load_price(&data).unwrap());
+    assert_eq!(price.agg.price, 12345);
+    assert_eq!(price.comp[31].latest.pub_slot, 42);
+
+    let mut data = account_data(size_of::<Product>(), VERSION_2, AccountType::Product);
+    data[48..52].copy_from_slice(&[3, b'f', b'o', b'o']);
+    let padded = with_trailing_zeros(&data);
+    let product = crate::load_product(&padded).unwrap();
+    assert_eq!(product, crate::load_product(&data).unwrap());
+    assert_eq!(product.get_attribute("foo"), Some(""));
+
+    let mut data = account_data(size_of::<Mapping>(), VERSION_2, AccountType::Mapping);
+    data[size_of::<Mapping>() - 32..].copy_from_slice(&[5; 32]);
+    let padded = with_trailing_zeros(&data);
+    let mapping = crate::load_mapping(&padded).unwrap();
+    assert_eq!(mapping, crate::load_mapping(&data).unwrap());
+    assert_eq!(mapping.products[MAP_TABLE_SIZE - 1], AccKey { val: [5; 32] });
+  }
+
+  #[test]
+  fn test_load_checks_size() {
+    let load_price = |data: &[u8]| crate::load_price(data).map(|_| ());
+    let set_word = |data: &mut Vec<u8>, offset: usize, val: u32| {
+      data[offset..offset + 4].copy_from_slice(&val.to_le_bytes());
+    };
+
+    // The used size may be smaller than the account.
+    let mut data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    set_word(&mut data, 12, 240);
+    assert_eq!(load_price(&data), Ok(()));
+    // Trailing data beyond the struct counts towards the account length.
+    data.extend_from_slice(&[0; 8]);
+    let len = data.len() as u32;
+    set_word(&mut data, 12, len);
+    assert_eq!(load_price(&data), Ok(()));
+    set_word(&mut data, 12, len + 1);
+    assert_eq!(load_price(&data), Err(PythError::InvalidAccountData));
+    set_word(&mut data, 12, 239);
+    assert_eq!(load_price(&data), Err(PythError::InvalidAccountData));
+
+    // At most 32 components.
+    let mut data = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    set_word(&mut data, 24, 32);
+    assert_eq!(load_price(&data), Ok(()));
+    set_word(&mut data, 24, 33);
+    assert_eq!(load_price(&data), Err(PythError::InvalidAccountData));
+
+    let mut data = account_data(size_of::<Product>(), VERSION_2, AccountType::Product);
+    set_word(&mut data, 12, PROD_HDR_SIZE as u32);
+    assert!(crate::load_product(&data).is_ok());
+    set_word(&mut data, 12, PROD_HDR_SIZE as u32 - 1);
+    assert_eq!(crate::load_product(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+    set_word(&mut data, 12, size_of::<Product>() as u32 + 1);
+    assert_eq!(crate::load_product(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+
+    let mut data = account_data(size_of::<Mapping>(), VERSION_2, AccountType::Mapping);
+    set_word(&mut data, 12, 56);
+    assert!(crate::load_mapping(&data).is_ok());
+    set_word(&mut data, 12, 0);
+    assert_eq!(crate::load_mapping(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+    set_word(&mut data, 12, u32::MAX);
+    assert_eq!(crate::load_mapping(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_load_account_too_small() {
+    let too_small = |expected: usize| Err(PythError::AccountTooSmall { expected, actual: expected - 1 });
+
+    let data = account_data(size_of::<Price>() - 1, VERSION_2, AccountType::Price);
+    assert_eq!(crate::load_price(&data).map(|_| ()), too_small(size_of::<Price>()));
+    assert_eq!(crate::load_price_feed(&data).map(|_| ()), too_small(size_of::<Price>()));
+    let data = account_data(size_of::<Product>() - 1, VERSION_2, AccountType::Product);
+    assert_eq!(crate::load_product(&data).map(|_| ()), too_small(size_of::<Product>()));
+    let data = account_data(size_of::<Mapping>() - 1, VERSION_2, AccountType::Mapping);
+    assert_eq!(crate::load_mapping(&data).map(|_| ()), too_small(size_of::<Mapping>()));
+
+    // Data too short to hold the header has no version to check.
+    assert_eq!(crate::load_price(&[0; 4]).map(|_| ()), Err(PythError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_enum_discriminants() {
+    use core::convert::TryFrom;
+
+    assert_eq!(AccountType::try_from(3), Ok(AccountType::Price));
+    assert_eq!(AccountType::try_from(4), Err(PythError::InvalidAccountData));
+    for status in [PriceStatus::Unknown, PriceStatus::Trading, PriceStatus::Halted, PriceStatus::Auction] {
+      assert_eq!(PriceStatus::try_from(status as u32), Ok(status));
+    }
+    assert_eq!(PriceStatus::try_from(4), Err(PythError::InvalidAccountData));
+    assert_eq!(crate::CorpAction::try_from(0), Ok(crate::CorpAction::NoCorpAct));
+    assert_eq!(crate::CorpAction::try_from(1), Err(PythError::InvalidAccountData));
+    assert_eq!(crate::PriceType::try_from(1), Ok(crate::PriceType::Price));
+    assert_eq!(crate::PriceType::try_from(u32::MAX), Err(PythError::InvalidAccountData));
+  }
+
+  #[test]
+  fn test_load_price_invalid_discriminant() {
+    let valid = account_data(size_of::<Price>(), VERSION_2, AccountType::Price);
+    assert!(crate::load_price(&valid).is_ok());
+
+    // ptype, agg.status, agg.corp_act, comp[0].agg.status and comp[31].latest.corp_act.
+    for offset in [16, 224, 228, crate::PRICE_HDR_SIZE + 48, crate::PRICE_HDR_SIZE + 31 * 96 + 84] {
+      let mut data = valid.clone();
+      data[offset..offset + 4].copy_from_slice(&9u32.to_le_bytes());
+      assert_eq!(crate::load_price(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+      assert_eq!(crate::load_price_feed(&data).map(|_| ()), Err(PythError::InvalidAccountData));
+    }
+  }
+
+  #[test]
+  fn test_get_ema_price() {
+    let mut price = trading_price(100, 1, -2, 1000);
+    price.ema_price.val = 98;
+    price.ema_confidence.val = 4;
+    assert_eq!(price.get_ema_price(), Some(PriceConf { price: 98, conf: 4, expo: -2 }));
+
+    // The confidence is only widened once the price diverges further than the EMA confidence.
+    price.agg.price = 102;
+    assert_eq!(price.get_ema_price(), Some(PriceConf { price: 98, conf: 4, expo: -2 }));
+    price.agg.price = 110;
+    assert_eq!(price.get_ema_price(), Some(PriceConf { price: 98, conf: 12, expo: -2 }));
+    price.agg.price = 80;
+    assert_eq!(price.get_ema_price(), Some(PriceConf { price: 98, conf: 18, expo: -2 }));
+
+    price.ema_price.val = i64::MIN;
+    price.agg.price = i64::MAX;
+    assert_eq!(price.get_ema_price(), Some(PriceConf { price: i64::MIN, conf: u64::MAX, expo: -2 }));
+    assert_eq!(price.to_owned_feed().get_ema_price(), price.get_ema_price());
+  }
+
+  #[test]
+  fn test_never_traded() {
+    // A new account has a zero price, and may be marked as trading before its first update.
+    let mut price = trading_price(0, 0, -2, 0);
+    assert_eq!(price.valid_slot, 0);
+    assert!(!price.is_initialized_and_trading());
+    assert_eq!(price.get_current_price(), None);
+    assert_eq!(price.get_price_no_older_than(0, 10), None);
+    assert_eq!(price.to_owned_feed().get_current_price(), None);
+
+    price.valid_slot = 1;
+    assert!(price.is_initialized_and_trading());
+    assert_eq!(price.get_current_price(), Some(PriceConf { price: 0, conf: 0, expo: -2 }));
+    assert_eq!(price.to_owned_feed().get_current_price(), Some(PriceConf { price: 0, conf: 0, expo: -2 }));
+
+    price.agg.status = PriceStatus::Halted;
+    assert!(!price.is_initialized_and_trading());
+  }
 }
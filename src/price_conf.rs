@@ -1,11 +1,55 @@
 use {
   borsh::{BorshDeserialize, BorshSerialize},
+  crate::PythError,
+  alloc::string::{String, ToString},
+  core::{cmp::Ordering, fmt},
 };
 
+/**
+ * The direction in which `PriceConf::scale_to_exponent_with` rounds a price that is not
+ * representable with the target exponent.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+  /// Round toward zero.
+  Truncate,
+  /// Round to the nearest representable value, with ties rounded away from zero.
+  Nearest,
+  /// Round toward positive infinity.
+  Ceil,
+  /// Round toward negative infinity.
+  Floor,
+}
+
 // Constants for working with pyth's number representation
-const PD_EXPO: i32 = -9;
-const PD_SCALE: u64 = 1_000_000_000;
-const MAX_PD_V_U64: u64 = (1 << 28) - 1;
+
+/// The number of extra decimal digits of precision that `PriceConf::div` gives its result, as an
+/// exponent. The exponent of the quotient of two normalized values is
+/// `numerator.expo + PD_EXPO - denominator.expo`.
+pub const PD_EXPO: i32 = -9;
+/// `10^-PD_EXPO`, the factor by which `PriceConf::div` scales its numerator.
+pub const PD_SCALE: u64 = 1_000_000_000;
+/// The largest magnitude of the price and confidence of a normalized `PriceConf` (2^28 - 1).
+/// `div` normalizes its inputs, which bounds the quotient and its confidence so that they fit into
+/// 64 bits even after scaling by `PD_SCALE`. Other methods, such as `mul` and the `*_to_exponent`
+/// variants, do not require normalized inputs.
+pub const MAX_PD_V_U64: u64 = (1 << 28) - 1;
+/// `MAX_PD_V_U64` as an i64: the largest price of a normalized `PriceConf`.
+pub const MAX_PD_V_I64: i64 = MAX_PD_V_U64 as i64;
+/// The smallest price of a normalized `PriceConf`.
+pub const MIN_PD_V_I64: i64 = -MAX_PD_V_I64;
+
+// Fixed-point logarithms used by `geometric_mean` have LOG_FRAC_BITS fractional bits.
+const LOG_FRAC_BITS: u32 = 62;
+const LOG_ONE: u128 = 1 << LOG_FRAC_BITS;
+// log2(10) and ln(2) with LOG_FRAC_BITS fractional bits, rounded to nearest
+const LOG2_10: i128 = 15_319_689_349_413_178_110;
+const LN_2: u128 = 3_196_577_161_300_663_915;
+// Relative confidences in `geometric_mean` are multiples of 1 / RELATIVE_CONF_SCALE, so that round
+// percentages are exact.
+const RELATIVE_CONF_SCALE: u128 = 1_000_000_000_000_000_000;
+// Relative weights in `combine_inverse_variance` have WEIGHT_FRAC_BITS fractional bits.
+const WEIGHT_FRAC_BITS: u32 = 48;
 
 /**
  * A price with a degree of uncertainty, represented as a price +- a confidence interval.
@@ -19,6 +63,9 @@ const MAX_PD_V_U64: u64 = (1 << 28) - 1;
  * PriceConf { price: 123, conf: 1, expo: 2 }; // represents 12300 +- 100
  * ```
  *
+ * Prefer `PriceConf::try_new` when the parts come from untrusted input, as it rejects values that
+ * no operation could normalize.
+ *
  * `PriceConf` supports a limited set of mathematical operations. All of these operations will
  * propagate any uncertainty in the arguments into the result. However, the uncertainty in the
  * result may overestimate the true uncertainty (by at most a factor of `sqrt(2)`) due to
@@ -27,7 +74,8 @@ const MAX_PD_V_U64: u64 = (1 << 28) - 1;
  * small that the price does not fit into an i64). Users of these methods should (1) select
  * their exponents to avoid this problem, and (2) handle the `None` case gracefully.
  */
-#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PriceConf {
   pub price: i64,
   pub conf: u64,
@@ -35,6 +83,17 @@ pub struct PriceConf {
 }
 
 impl PriceConf {
+  /**
+   * Construct a `PriceConf` from its parts, checking that it can be normalized. Returns
+   * `PythError::ExponentOverflow` if normalizing would overflow the exponent, in which case the
+   * arithmetic operations below would fail on the value.
+   */
+  pub fn try_new(price: i64, conf: u64, expo: i32) -> Result<PriceConf, PythError> {
+    let result = PriceConf { price, conf, expo };
+    result.try_normalize()?;
+    Ok(result)
+  }
+
   /**
    * Divide this price by `other` while propagating the uncertainty in both prices into the result.
    *
@@ -42,10 +101,53 @@ impl PriceConf {
    * `self` and `other` are normalized, the exponent is `self.expo + PD_EXPO - other.expo` (i.e.,
    * the fraction has `PD_EXPO` digits of additional precision). If they are not normalized,
    * this method will normalize them, resulting in an unpredictable result exponent.
-   * If the result is used in a context that requires a specific exponent, please call
-   * `scale_to_exponent` on it.
+   * If the result is used in a context that requires a specific exponent, please use
+   * `div_with_expo` instead, which rounds the quotient once at that exponent rather than calling
+   * `scale_to_exponent` on the result.
+   *
+   * The price of the result is rounded toward zero. See `div_floor` and `div_ceil` to round it in
+   * a specific direction instead.
    */
   pub fn div(&self, other: &PriceConf) -> Option<PriceConf> {
+    self.div_with_remainder(other).map(|(result, _)| result)
+  }
+
+  /**
+   * Same as `div`, except that the price of the result is rounded down (toward negative infinity).
+   *
+   * Rounding down protects a lender when valuing a borrower's collateral: the collateral is never
+   * worth more than the exact quotient. Use `div_ceil` to value the borrower's debt instead.
+   */
+  pub fn div_floor(&self, other: &PriceConf) -> Option<PriceConf> {
+    let (result, remainder_sign) = self.div_with_remainder(other)?;
+    if remainder_sign < 0 {
+      Some(PriceConf { price: result.price.checked_sub(1)?, ..result })
+    } else {
+      Some(result)
+    }
+  }
+
+  /**
+   * Same as `div`, except that the price of the result is rounded up (toward positive infinity).
+   *
+   * Rounding up protects a lender when valuing a borrower's debt: the debt is never worth less
+   * than the exact quotient. Conversely, a borrower is favored by `div_floor` on their debt and
+   * `div_ceil` on their collateral, so these must not be swapped.
+   */
+  pub fn div_ceil(&self, other: &PriceConf) -> Option<PriceConf> {
+    let (result, remainder_sign) = self.div_with_remainder(other)?;
+    if remainder_sign > 0 {
+      Some(PriceConf { price: result.price.checked_add(1)?, ..result })
+    } else {
+      Some(result)
+    }
+  }
+
+  /**
+   * Helper function implementing `div`. Also returns the sign of the part of the exact quotient
+   * that was truncated from the price, which is 0 if the division is exact.
+   */
+  fn div_with_remainder(&self, other: &PriceConf) -> Option<(PriceConf, i64)> {
     // PriceConf is not guaranteed to store its price/confidence in normalized form.
     // Normalize them here to bound the range of price/conf, which is required to perform
     // arithmetic operations.
@@ -64,6 +166,11 @@ impl PriceConf {
     // Uses at most 57 bits
     let midprice = base_price.checked_mul(PD_SCALE)?.checked_div(other_price)?;
     let midprice_expo = base.expo.checked_sub(other.expo)?.checked_add(PD_EXPO)?;
+    // The normalization above guarantees that this holds, but the conversion to i64 below must
+    // not rely on that invariant.
+    if midprice > i64::MAX as u64 {
+      return None;
+    }
 
     // Compute the confidence interval.
     // This code uses the 1-norm instead of the 2-norm for computational reasons.
@@ -75,27 +182,54 @@ impl PriceConf {
     // This quantity is at most a factor of sqrt(2) greater than the correct result, which
     // shouldn't matter considering that confidence intervals are typically ~0.1% of the price.
 
-    // This uses 57 bits and has an exponent of PD_EXPO.
-    let other_confidence_pct: u64 = other.conf.checked_mul(PD_SCALE)?.checked_div(other_price)?;
-
-    // first term is 57 bits, second term is 57 + 58 - 29 = 86 bits. Same exponent as the midprice.
+    // Both terms are rounded up so that the uncertainty of the result is never under-reported.
+    // first term is 57 bits, second term is at most 57 bits, and its numerator uses at most
+    // 27 + 30 + 28 = 85 bits. Same exponent as the midprice.
     // Note: the computation of the 2nd term consumes about 3k ops. We may want to optimize this.
-    let conf = (base.conf.checked_mul(PD_SCALE)?.checked_div(other_price)? as u128).checked_add(
-      (other_confidence_pct as u128).checked_mul(midprice as u128)?.checked_div(PD_SCALE as u128)?)?;
+    let conf = PriceConf::ceil_div(
+      (base.conf as u128).checked_mul(PD_SCALE as u128)?,
+      other_price as u128
+    )?.checked_add(PriceConf::ceil_div(
+      (base_price as u128).checked_mul(PD_SCALE as u128)?.checked_mul(other.conf as u128)?,
+      (other_price as u128).checked_mul(other_price as u128)?
+    )?)?;
+
+    let sign = base_sign.checked_mul(other_sign)?;
+    let remainder_sign = if base_price.checked_mul(PD_SCALE)?.checked_rem(other_price)? != 0 {
+      sign
+    } else {
+      0
+    };
 
     // Note that this check only fails if an argument's confidence interval was >> its price,
     // in which case None is a reasonable result, as we have essentially 0 information about the price.
     if conf < (u64::MAX as u128) {
-      Some(PriceConf {
-        price: (midprice as i64).checked_mul(base_sign)?.checked_mul(other_sign)?,
-        conf: conf as u64,
-        expo: midprice_expo,
-      })
+      Some((
+        PriceConf {
+          price: (midprice as i64).checked_mul(sign)?,
+          conf: conf as u64,
+          expo: midprice_expo,
+        },
+        remainder_sign,
+      ))
     } else {
       None
     }
   }
 
+  /**
+   * Compute `1 / self`, e.g., to turn a USD/SOL price into a SOL/USD price, and scale the result
+   * to `result_expo`. The relative uncertainty is preserved, i.e., the confidence of the result
+   * is `(1 / price) * (conf / price)`. Returns `None` if the price is zero, or if the result is
+   * not representable with `result_expo`.
+   */
+  pub fn reciprocal(&self, result_expo: i32) -> Option<PriceConf> {
+    // Represent one with as many digits as a normalized price, so that `div` keeps the full
+    // precision of its fixed-point division.
+    let one = PriceConf { price: 100_000_000, conf: 0, expo: -8 };
+    one.div(self)?.scale_to_exponent(result_expo)
+  }
+
   /**
    * Add `other` to this, propagating uncertainty in both prices. Requires both
    * `PriceConf`s to have the same exponent -- use `scale_to_exponent` on the arguments
@@ -105,6 +239,18 @@ impl PriceConf {
    */
   pub fn add(&self, other: &PriceConf) -> Option<PriceConf> {
     assert_eq!(self.expo, other.expo);
+    self.add_same_expo(other)
+  }
+
+  /**
+   * Same as `add`, except that it returns `None` instead of panicking if the exponents differ.
+   * Neither argument is scaled or normalized, so this is the cheapest way to add values that are
+   * already at the same exponent. Use `add_to_exponent` to add values with different exponents.
+   */
+  pub fn add_same_expo(&self, other: &PriceConf) -> Option<PriceConf> {
+    if self.expo != other.expo {
+      return None;
+    }
 
     let price = self.price.checked_add(other.price)?;
     // The conf should technically be sqrt(a^2 + b^2), but that's harder to compute.
@@ -116,58 +262,507 @@ impl PriceConf {
     })
   }
 
-  /** Multiply this `PriceConf` by a constant `c * 10^e`. */
+  /**
+   * Add `other` to this `PriceConf` in place, as in `add_same_expo`. This avoids rebinding a
+   * local in accumulation loops such as `Price::price_basket`. Returns `None` and leaves `self`
+   * unchanged if the exponents differ or the sum overflows.
+   */
+  pub fn add_in_place(&mut self, other: &PriceConf) -> Option<()> {
+    *self = self.add_same_expo(other)?;
+    Some(())
+  }
+
+  /**
+   * Subtract `other` from this, propagating uncertainty in both prices. Requires both
+   * `PriceConf`s to have the same exponent -- use `scale_to_exponent` on the arguments
+   * if necessary.
+   */
+  pub fn sub(&self, other: &PriceConf) -> Option<PriceConf> {
+    assert_eq!(self.expo, other.expo);
+
+    let price = self.price.checked_sub(other.price)?;
+    // The uncertainties add up exactly as in `add`.
+    let conf = self.conf.checked_add(other.conf)?;
+    Some(PriceConf {
+      price,
+      conf,
+      expo: self.expo,
+    })
+  }
+
+  /**
+   * Multiply this `PriceConf` by a constant `c * 10^e`.
+   *
+   * Since the constant is exact, the arguments are not normalized (unlike `mul`). If the product
+   * does not fit into the fixed-point representation, its least significant digits are dropped
+   * (rounding as in `scale_to_exponent`) and the exponent of the result increases accordingly.
+   * Returns `None` if the exponent of the result overflows.
+   */
   pub fn cmul(&self, c: i64, e: i32) -> Option<PriceConf> {
-    self.mul(&PriceConf { price: c, conf: 0, expo: e })
+    let expo = self.expo.checked_add(e)?;
+    let c_abs = c.unsigned_abs();
+
+    // Fast path: the exact product fits into 64 bits.
+    if let (Some(price), Some(conf)) = (self.price.checked_mul(c), self.conf.checked_mul(c_abs)) {
+      return Some(PriceConf { price, conf, expo });
+    }
+
+    // Each product uses at most 128 bits.
+    PriceConf::from_wide(
+      (self.price as i128).checked_mul(c as i128)?,
+      (self.conf as u128).checked_mul(c_abs as u128)?,
+      expo
+    )
   }
 
-  /** Multiply this `PriceConf` by `other`, propagating any uncertainty. */
+  /**
+   * Multiply this `PriceConf` by `other`, propagating any uncertainty.
+   *
+   * The product is computed exactly using 128-bit intermediates. If it does not fit into the
+   * fixed-point representation, its least significant digits are dropped (rounding as in
+   * `scale_to_exponent`) and the exponent of the result increases accordingly.
+   */
   pub fn mul(&self, other: &PriceConf) -> Option<PriceConf> {
-    // PriceConf is not guaranteed to store its price/confidence in normalized form.
-    // Normalize them here to bound the range of price/conf, which is required to perform
-    // arithmetic operations.
-    let base = self.normalize()?;
-    let other = other.normalize()?;
-
-    // These use at most 27 bits each
-    let (base_price, base_sign) = PriceConf::to_unsigned(base.price);
+    let (base_price, base_sign) = PriceConf::to_unsigned(self.price);
     let (other_price, other_sign) = PriceConf::to_unsigned(other.price);
 
-    // Uses at most 27*2 = 54 bits
-    let midprice = base_price.checked_mul(other_price)?;
-    let midprice_expo = base.expo.checked_add(other.expo)?;
+    // Uses at most 64*2 = 128 bits, though the product of two prices cannot exceed 2^126
+    let midprice = (base_price as u128).checked_mul(other_price as u128)?;
+    let midprice_expo = self.expo.checked_add(other.expo)?;
 
     // Compute the confidence interval.
     // This code uses the 1-norm instead of the 2-norm for computational reasons.
     // Note that this simplifies: pq * (a/p + b/q) = qa + pb
-    // 27*2 + 1 bits
-    let conf = base.conf.checked_mul(other_price)?.checked_add(other.conf.checked_mul(base_price)?)?;
+    // Each term uses at most 64+64 bits, so the sum is checked
+    let conf = (self.conf as u128).checked_mul(other_price as u128)?
+      .checked_add((other.conf as u128).checked_mul(base_price as u128)?)?;
 
-    Some(PriceConf {
-      price: (midprice as i64).checked_mul(base_sign)?.checked_mul(other_sign)?,
+    PriceConf::from_wide(
+      (midprice as i128).checked_mul((base_sign * other_sign) as i128)?,
+      conf,
+      midprice_expo
+    )
+  }
+
+  /**
+   * Multiply this `PriceConf` by `other` in place, as in `mul`. This avoids rebinding a local in
+   * accumulation loops. Returns `None` and leaves `self` unchanged if `mul` would return `None`.
+   */
+  pub fn mul_in_place(&mut self, other: &PriceConf) -> Option<()> {
+    *self = self.mul(other)?;
+    Some(())
+  }
+
+  /**
+   * Raise this price to the power `n` and scale the result to `result_expo`, propagating the
+   * uncertainty as in `mul`. `powi(0, ..)` returns one.
+   *
+   * The power is computed by repeated squaring, so this method uses `O(log n)` multiplications.
+   * Returns `None` if an intermediate product overflows, or if the result is not representable
+   * with `result_expo`.
+   */
+  pub fn powi(&self, n: u32, result_expo: i32) -> Option<PriceConf> {
+    let mut result = PriceConf { price: 1, conf: 0, expo: 0 };
+    let mut base = *self;
+    let mut n = n;
+    while n > 0 {
+      if n & 1 == 1 {
+        result = result.mul(&base)?;
+      }
+      n >>= 1;
+      if n > 0 {
+        base = base.mul(&base)?;
+      }
+    }
+    result.scale_to_exponent(result_expo)
+  }
+
+  /**
+   * Multiply this price by `other` and express the result with exponent `target_expo`.
+   *
+   * This is equivalent to `self.mul(other)?.scale_to_exponent(target_expo)`, except that the
+   * exact product is scaled to `target_expo` directly, so the result is rounded only once.
+   * Returns `None` if the result does not fit into the numeric representation at `target_expo`.
+   */
+  pub fn mul_to_exponent(&self, other: &PriceConf, target_expo: i32) -> Option<PriceConf> {
+    let (base_price, base_sign) = PriceConf::to_unsigned(self.price);
+    let (other_price, other_sign) = PriceConf::to_unsigned(other.price);
+
+    // Same computation as `mul`, see there for the bounds on the intermediates.
+    let midprice = (base_price as u128).checked_mul(other_price as u128)?;
+    let conf = (self.conf as u128).checked_mul(other_price as u128)?
+      .checked_add((other.conf as u128).checked_mul(base_price as u128)?)?;
+
+    PriceConf::wide_to_exponent(
+      (midprice as i128).checked_mul((base_sign * other_sign) as i128)?,
+      conf,
+      self.expo.checked_add(other.expo)?,
+      target_expo
+    )
+  }
+
+  /**
+   * Divide this price by `other` and express the result with exponent `target_expo`.
+   *
+   * Unlike `div`, this method does not normalize its inputs, and computes the quotient at
+   * `target_expo` directly using 128-bit intermediates. The price is rounded to nearest and the
+   * confidence is rounded up, as in `scale_to_exponent`. Returns `None` if `other.price` is 0, if
+   * a nonzero quotient rounds to 0, or if the result (or an intermediate value) does not fit into
+   * the numeric representation.
+   */
+  pub fn div_to_exponent(&self, other: &PriceConf, target_expo: i32) -> Option<PriceConf> {
+    let (base_price, base_sign) = PriceConf::to_unsigned(self.price);
+    let (other_price, other_sign) = PriceConf::to_unsigned(other.price);
+    if other_price == 0 {
+      return None;
+    }
+
+    // The quotient of the raw values has exponent `self.expo - other.expo`, so it needs to be
+    // multiplied by 10^shift to be expressed with exponent `target_expo`. The scale factor is
+    // applied to the numerator or the denominator depending on its sign.
+    let shift = self.expo.checked_sub(other.expo)?.checked_sub(target_expo)?;
+    let scale = 10u128.checked_pow(shift.unsigned_abs())?;
+    let (numerator_scale, denominator) = if shift >= 0 {
+      (scale, other_price as u128)
+    } else {
+      (1, (other_price as u128).checked_mul(scale)?)
+    };
+
+    let midprice = PriceConf::div_nearest(
+      (base_price as u128).checked_mul(numerator_scale)?,
+      denominator
+    )?;
+    if midprice == 0 && base_price != 0 {
+      return None;
+    }
+
+    // Compute the confidence interval as in `div`, i.e., conf = a/q + p*b/q^2 with the 1-norm.
+    // Both terms are rounded up so that the uncertainty is never under-reported.
+    let conf = PriceConf::ceil_div((self.conf as u128).checked_mul(numerator_scale)?, denominator)?
+      .checked_add(PriceConf::ceil_div(
+        (base_price as u128).checked_mul(other.conf as u128)?.checked_mul(numerator_scale)?,
+        denominator.checked_mul(other_price as u128)?
+      )?)?;
+
+    PriceConf::wide_to_exponent(
+      (midprice as i128).checked_mul((base_sign * other_sign) as i128)?,
       conf,
-      expo: midprice_expo,
+      target_expo,
+      target_expo
+    )
+  }
+
+  /**
+   * Divide this price by `other`, expressing the result with the caller's `result_expo` in a
+   * single step, so that the quotient is only rounded once. This is more precise than calling
+   * `scale_to_exponent` on the result of `div`. See `div_to_exponent` for the rounding and the
+   * cases in which this returns `None`.
+   */
+  pub fn div_with_expo(&self, other: &PriceConf, result_expo: i32) -> Option<PriceConf> {
+    self.div_to_exponent(other, result_expo)
+  }
+
+  /**
+   * Add `other` to this price and express the result with exponent `target_expo`.
+   *
+   * Unlike `add`, the arguments may have different exponents. Both are expressed exactly at the
+   * smaller of their exponents before being added, so the result is rounded only once. Returns
+   * `None` if the result does not fit into the numeric representation at `target_expo`, or if the
+   * exponents are too far apart to align the arguments within 128 bits.
+   */
+  pub fn add_to_exponent(&self, other: &PriceConf, target_expo: i32) -> Option<PriceConf> {
+    let expo = self.expo.min(other.expo);
+    let (base_price, base_conf) = PriceConf::to_wide_at_exponent(self, expo)?;
+    let (other_price, other_conf) = PriceConf::to_wide_at_exponent(other, expo)?;
+
+    PriceConf::wide_to_exponent(
+      base_price.checked_add(other_price)?,
+      base_conf.checked_add(other_conf)?,
+      expo,
+      target_expo
+    )
+  }
+
+  /**
+   * Clamp this price into the band from `min` to `max`. The confidences of `min` and `max` are
+   * ignored, i.e., the bounds are treated as exact.
+   *
+   * All three arguments are expressed with the smallest of their exponents, which is also the
+   * exponent of the result. If the price lies outside the band, the result is the nearest bound,
+   * and its confidence is widened by the distance the price moved, so that the interval of the
+   * result still contains the interval of `self`. Returns `None` if the exponents cannot be
+   * aligned, if `min > max`, or if the widened confidence overflows.
+   */
+  pub fn clamp(&self, min: &PriceConf, max: &PriceConf) -> Option<PriceConf> {
+    let expo = self.expo.min(min.expo).min(max.expo);
+    // Scaling to a smaller exponent is exact.
+    let x = self.scale_to_exponent(expo)?;
+    let min = min.scale_to_exponent(expo)?.price;
+    let max = max.scale_to_exponent(expo)?.price;
+    if min > max {
+      return None;
+    }
+
+    let bound = if x.price < min {
+      min
+    } else if x.price > max {
+      max
+    } else {
+      return Some(x);
+    };
+    // The distance between two i64 values always fits into a u64.
+    let distance = (x.price as i128 - bound as i128).unsigned_abs() as u64;
+    Some(PriceConf {
+      price: bound,
+      conf: x.conf.checked_add(distance)?,
+      expo,
     })
   }
 
+  /**
+   * Compare the central price of this value with that of `other`, ignoring the confidence
+   * intervals. The two prices are aligned to the smaller of their exponents before comparing, so
+   * `123 * 10^-2` and `1230 * 10^-3` compare as equal even though they are not `==` (which
+   * compares the stored representation, including the confidence). This is why `PriceConf` does
+   * not implement `PartialOrd`. Returns `None` if aligning the exponents overflows.
+   */
+  pub fn cmp_price(&self, other: &PriceConf) -> Option<Ordering> {
+    let expo = self.expo.min(other.expo);
+    let self_price = PriceConf::price_at_lower_exponent(self.price, self.expo, expo)?;
+    let other_price = PriceConf::price_at_lower_exponent(other.price, other.expo, expo)?;
+    Some(self_price.cmp(&other_price))
+  }
+
+  /**
+   * Get the element of `prices` with the greatest central price, ignoring the confidence
+   * intervals. The prices are compared as in `cmp_price`, after aligning all of them to the
+   * smallest exponent among them, and the element is returned unchanged. The first of several
+   * equal maxima is returned. Returns `None` if `prices` is empty or aligning the exponents
+   * overflows.
+   */
+  pub fn max_of(prices: &[PriceConf]) -> Option<PriceConf> {
+    PriceConf::extreme_of(prices, Ordering::Greater)
+  }
+
+  /** Get the element of `prices` with the least central price. See `max_of`. */
+  pub fn min_of(prices: &[PriceConf]) -> Option<PriceConf> {
+    PriceConf::extreme_of(prices, Ordering::Less)
+  }
+
+  /**
+   * Compute the weighted mean of `prices`, where each entry is a `(price, weight)` pair. The
+   * confidence of the result is the weighted mean of the input confidences.
+   *
+   * The inputs are scaled to the smallest exponent among them, which is also the exponent of the
+   * result. The price is rounded to nearest and the confidence is rounded up. Returns `None` if
+   * `prices` is empty, the total weight is zero, or an intermediate value overflows.
+   */
+  pub fn weighted_mean(prices: &[(PriceConf, u64)]) -> Option<PriceConf> {
+    let expo = prices.iter().map(|(price, _)| price.expo).min()?;
+
+    let mut price_sum: i128 = 0;
+    let mut conf_sum: u128 = 0;
+    let mut weight_sum: u128 = 0;
+    for (price, weight) in prices {
+      let scaled = price.scale_to_exponent(expo)?;
+      price_sum = price_sum.checked_add((scaled.price as i128).checked_mul(*weight as i128)?)?;
+      conf_sum = conf_sum.checked_add((scaled.conf as u128).checked_mul(*weight as u128)?)?;
+      weight_sum = weight_sum.checked_add(*weight as u128)?;
+    }
+
+    // Round half away from zero, which is symmetric in the sign of the price.
+    let price_abs = price_sum.unsigned_abs();
+    let mut mean_abs = price_abs.checked_div(weight_sum)?;
+    if price_abs.checked_rem(weight_sum)?.checked_mul(2)? >= weight_sum {
+      mean_abs = mean_abs.checked_add(1)?;
+    }
+    let mean = if price_sum < 0 { -(mean_abs as i128) } else { mean_abs as i128 };
+
+    PriceConf::from_wide(mean, PriceConf::ceil_div(conf_sum, weight_sum)?, expo)
+  }
+
+  /**
+   * Compute the geometric mean of `prices`, i.e., the n-th root of their product, with exponent
+   * `result_expo`. The relative confidence of the result (`conf / price`) is the mean of the
+   * relative confidences of the inputs, which is how a small relative error in each input
+   * propagates to the geometric mean.
+   *
+   * The mean is computed as `2^(mean of log2(price))` using fixed-point logarithms with 62
+   * fractional bits. Beyond rounding the price to `result_expo` (to nearest), this gives a relative
+   * error of about `10^-16 + d * 10^-19`, where `d` is the distance between the mean exponent of
+   * the inputs and `result_expo`, since the rounding error of log2(10) is multiplied by it. For
+   * exponents of similar size, the error is negligible unless the result has more than 16
+   * significant digits; at the ends of the exponent range (`d` close to 2^32), it grows to about
+   * 2^-32. The confidence is rounded up.
+   *
+   * Returns `None` if `prices` is empty, any price is zero or negative, or the result does not
+   * fit into `result_expo` (including a price that would round to 0).
+   */
+  pub fn geometric_mean(prices: &[PriceConf], result_expo: i32) -> Option<PriceConf> {
+    if prices.is_empty() {
+      return None;
+    }
+
+    let mut log_sum: i128 = 0;
+    // The sum of the relative confidences, as multiples of 1 / RELATIVE_CONF_SCALE
+    let mut relative_conf_sum: u128 = 0;
+    for price in prices {
+      if price.price <= 0 {
+        return None;
+      }
+      let log = PriceConf::log2_fixed(price.price as u64)
+        .checked_add((price.expo as i128).checked_mul(LOG2_10)?)?;
+      log_sum = log_sum.checked_add(log)?;
+      let relative_conf = PriceConf::ceil_div((price.conf as u128) * RELATIVE_CONF_SCALE, price.price as u128)?;
+      relative_conf_sum = relative_conf_sum.checked_add(relative_conf)?;
+    }
+    let n = prices.len() as u128;
+
+    // log2 of the result, as a multiple of 10^result_expo
+    let log_mean = log_sum.div_euclid(n as i128);
+    let log_result = log_mean.checked_sub((result_expo as i128).checked_mul(LOG2_10)?)?;
+    let price = PriceConf::exp2_fixed(log_result)?;
+    if price == 0 || price > i64::MAX as u128 {
+      return None;
+    }
+
+    // conf = price * relative_conf, split so that the product cannot overflow.
+    let relative_conf = PriceConf::ceil_div(relative_conf_sum, n)?;
+    let conf = (relative_conf / RELATIVE_CONF_SCALE).checked_mul(price)?.checked_add(
+      PriceConf::ceil_div((relative_conf % RELATIVE_CONF_SCALE) * price, RELATIVE_CONF_SCALE)?,
+    )?;
+    if conf > u64::MAX as u128 {
+      return None;
+    }
+
+    Some(PriceConf { price: price as i64, conf: conf as u64, expo: result_expo })
+  }
+
+  /**
+   * Combine independent measurements of the same price, e.g., from redundant oracles, weighting
+   * each by the inverse of its variance (`1 / conf^2`). The price of the result is
+   * `sum(price / conf^2) / sum(1 / conf^2)` and its confidence is `1 / sqrt(sum(1 / conf^2))`,
+   * which is smaller than the confidence of every input (up to rounding).
+   *
+   * The weights are computed relative to the smallest confidence with 48 fractional bits, so a
+   * source whose confidence is more than 2^24 times that of the tightest one is ignored. Inputs
+   * with a zero confidence cannot be weighted and are ignored as well. The price is rounded to
+   * nearest and the confidence is rounded up to `result_expo`.
+   *
+   * Returns `None` if `prices` is empty, all confidences are zero, or an intermediate value or
+   * the result does not fit (including a price that would round to 0).
+   */
+  pub fn combine_inverse_variance(prices: &[PriceConf], result_expo: i32) -> Option<PriceConf> {
+    let weighted = || prices.iter().filter(|price| price.conf != 0);
+    // Inputs that are ignored do not affect the exponent the others are aligned to. This also
+    // returns `None` if there are no weighted inputs.
+    let expo = weighted().map(|price| price.expo).min()?;
+    let mut min_conf = u64::MAX as u128;
+    for price in weighted() {
+      min_conf = min_conf.min(price.scale_to_exponent(expo)?.conf as u128);
+    }
+
+    // The weight of each input relative to the tightest one, (min_conf / conf)^2, in (0, 1].
+    let mut price_sum: i128 = 0;
+    let mut weight_sum: u128 = 0;
+    for price in weighted() {
+      let price = price.scale_to_exponent(expo)?;
+      let ratio = (min_conf << WEIGHT_FRAC_BITS) / price.conf as u128;
+      let weight = (ratio * ratio) >> WEIGHT_FRAC_BITS;
+      price_sum = price_sum.checked_add((price.price as i128).checked_mul(weight as i128)?)?;
+      weight_sum = weight_sum.checked_add(weight)?;
+    }
+
+    // Both the price and the confidence are converted from `expo` to `result_expo` before the
+    // final division, so that they are only rounded once.
+    let (num_scale, den_scale) = if result_expo <= expo {
+      (10u128.checked_pow(expo.checked_sub(result_expo)? as u32)?, 1)
+    } else {
+      (1, 10u128.checked_pow(result_expo.checked_sub(expo)? as u32)?)
+    };
+
+    let price_abs = PriceConf::div_nearest(
+      price_sum.unsigned_abs().checked_mul(num_scale)?,
+      weight_sum.checked_mul(den_scale)?,
+    )?;
+    if price_abs > i64::MAX as u128 + 1 || (price_abs == 0 && price_sum != 0) {
+      return None;
+    }
+    let price = if price_sum < 0 { -(price_abs as i128) } else { price_abs as i128 };
+    if price > i64::MAX as i128 {
+      return None;
+    }
+
+    // conf = min_conf / sqrt(weight_sum / 2^WEIGHT_FRAC_BITS), where the square root of
+    // weight_sum * 2^(WEIGHT_FRAC_BITS + 16) has WEIGHT_FRAC_BITS + 8 fractional bits.
+    let root = PriceConf::isqrt(weight_sum.checked_shl(WEIGHT_FRAC_BITS + 16)?);
+    let conf = PriceConf::ceil_div(
+      min_conf.checked_mul(1 << (WEIGHT_FRAC_BITS + 8))?.checked_mul(num_scale)?,
+      root.checked_mul(den_scale)?,
+    )?;
+    if conf > u64::MAX as u128 {
+      return None;
+    }
+
+    Some(PriceConf { price: price as i64, conf: conf as u64, expo: result_expo })
+  }
+
   /**
    * Get a copy of this struct where the price and confidence
    * have been normalized to be between `MIN_PD_V_I64` and `MAX_PD_V_I64`.
+   * Returns `None` if the exponent overflows; see `try_normalize`.
    */
   pub fn normalize(&self) -> Option<PriceConf> {
+    self.try_normalize().ok()
+  }
+
+  /**
+   * Same as `normalize`, but returns `None` if normalizing drops digits of the price and fewer than
+   * `min_sig_digits` significant digits of the price remain. For example, a price of 123456789012
+   * with a confidence of 10 keeps 9 significant digits, while a price of 5 with a confidence of
+   * 10^12 is normalized to 0 and keeps none. This lets callers reject inputs that are too extreme
+   * to compute on safely. A result with more than 9 significant digits is out of range for a
+   * normalized price, so any lossy normalization is rejected if `min_sig_digits` exceeds 9.
+   *
+   * A price that does not need to be normalized, or a zero price, is always returned unchanged.
+   */
+  pub fn normalize_keeping_precision(&self, min_sig_digits: u32) -> Option<PriceConf> {
+    let normalized = self.normalize()?;
+    if normalized.expo == self.expo || self.price == 0 {
+      return Some(normalized);
+    }
+
+    let mut digits = 0;
+    let mut price = normalized.price.unsigned_abs();
+    while price > 0 {
+      price /= 10;
+      digits += 1;
+    }
+    if digits < min_sig_digits {
+      return None;
+    }
+    Some(normalized)
+  }
+
+  /**
+   * Same as `normalize`, but returns `PythError::ExponentOverflow` if the exponent of the
+   * result does not fit in an i32.
+   */
+  pub fn try_normalize(&self) -> Result<PriceConf, PythError> {
     // signed division is very expensive in op count
     let (mut p, s) = PriceConf::to_unsigned(self.price);
     let mut c = self.conf;
     let mut e = self.expo;
 
     while p > MAX_PD_V_U64 || c > MAX_PD_V_U64 {
-      p = p.checked_div(10)?;
-      c = c.checked_div(10)?;
-      e = e.checked_add(1)?;
+      p /= 10;
+      c /= 10;
+      e = e.checked_add(1).ok_or(PythError::ExponentOverflow)?;
     }
 
-    Some(PriceConf {
-      price: (p as i64).checked_mul(s)?,
+    // p is at most MAX_PD_V_U64, so this cannot overflow
+    Ok(PriceConf {
+      price: (p as i64) * s,
       conf: c,
       expo: e,
     })
@@ -178,27 +773,47 @@ impl PriceConf {
    * this number is outside the range of numbers representable in `target_expo`, which will
    * happen if `target_expo` is too small.
    *
-   * Warning: if `target_expo` is significantly larger than the current exponent, this function
-   * will return 0 +- 0.
+   * When scaling to a larger exponent, the price is rounded to the nearest representable value
+   * (with ties rounded away from zero), and the confidence is rounded up so that the uncertainty
+   * of the result is never under-reported.
+   *
+   * A nonzero price never becomes zero: if `target_expo` is so large that the price would round
+   * to 0, this function returns `None` instead.
+   *
+   * This is equivalent to `scale_to_exponent_with(target_expo, Rounding::Nearest)`.
    */
   pub fn scale_to_exponent(
     &self,
     target_expo: i32,
+  ) -> Option<PriceConf> {
+    self.scale_to_exponent_with(target_expo, Rounding::Nearest)
+  }
+
+  /**
+   * Same as `scale_to_exponent`, except that when scaling to a larger exponent, the price is
+   * rounded according to `rounding`. The confidence is always rounded up, whatever the rounding
+   * of the price, so that the uncertainty of the result is never under-reported.
+   *
+   * As in `scale_to_exponent`, this function returns `None` rather than rounding a nonzero price
+   * to 0, which can happen with `Rounding::Truncate` and, depending on the sign of the price,
+   * with `Rounding::Floor` or `Rounding::Ceil`.
+   */
+  pub fn scale_to_exponent_with(
+    &self,
+    target_expo: i32,
+    rounding: Rounding,
   ) -> Option<PriceConf> {
     let mut delta = target_expo.checked_sub(self.expo)?;
     if delta >= 0 {
-      let mut p = self.price;
-      let mut c = self.conf;
-      // 2nd term is a short-circuit to bound op consumption
-      while delta > 0 && (p != 0 || c != 0) {
-        p = p.checked_div(10)?;
-        c = c.checked_div(10)?;
-        delta = delta.checked_sub(1)?;
+      let (p, c) = PriceConf::drop_digits(self.price as i128, self.conf as u128, delta, rounding)?;
+      if p == 0 && self.price != 0 {
+        return None;
       }
 
+      // The result fits, as rounding can only overflow if no digit has been dropped.
       Some(PriceConf {
-        price: p,
-        conf: c,
+        price: p as i64,
+        conf: c as u64,
         expo: target_expo,
       })
     } else {
@@ -212,36 +827,692 @@ impl PriceConf {
         delta = delta.checked_add(1)?;
       }
 
-      Some(PriceConf {
-        price: p,
-        conf: c,
-        expo: target_expo,
-      })
-    }
+      Some(PriceConf {
+        price: p,
+        conf: c,
+        expo: target_expo,
+      })
+    }
+  }
+
+  /**
+   * Get this price/confidence with exponent `target_expo`, as a replacement for
+   * `self.normalize()?.scale_to_exponent(target_expo)`.
+   *
+   * Normalizing first only drops digits (truncating both the price and the confidence), and those
+   * digits never change whether the result is representable with `target_expo`. This method skips
+   * it and scales directly to `target_expo`, so the result is rounded once as in
+   * `scale_to_exponent`. When `target_expo` is smaller than `self.expo`, the result is computed
+   * with a single multiplication rather than one per digit. Returns `None` if the result is not
+   * representable with `target_expo`.
+   */
+  pub fn to_exponent_normalized(&self, target_expo: i32) -> Option<PriceConf> {
+    let delta = target_expo.checked_sub(self.expo)?;
+    if delta >= 0 {
+      return self.scale_to_exponent(target_expo);
+    }
+
+    // Zeros scale to any exponent, even one where the multiplier would overflow.
+    let n = delta.unsigned_abs();
+    let price = match self.price {
+      0 => 0,
+      p => p.checked_mul(10i64.checked_pow(n)?)?,
+    };
+    let conf = match self.conf {
+      0 => 0,
+      c => c.checked_mul(10u64.checked_pow(n)?)?,
+    };
+    Some(PriceConf { price, conf, expo: target_expo })
+  }
+
+  /**
+   * Same as `scale_to_exponent`, except that values that are not representable with `target_expo`
+   * saturate instead of returning `None`: a price that is too large in magnitude becomes `i64::MAX`
+   * or `i64::MIN` (depending on its sign) and a confidence that is too large becomes `u64::MAX`,
+   * while a nonzero price that is too small becomes 0.
+   *
+   * The result of this method may be arbitrarily far from the true value, so it is intended for
+   * display and logging only. It must not be used for any computation that relies on the price.
+   */
+  pub fn scale_to_exponent_saturating(&self, target_expo: i32) -> PriceConf {
+    // The price and the confidence are scaled independently, so each of them saturates separately.
+    let scaling_up = target_expo < self.expo;
+    let price = if self.price == 0 {
+      0
+    } else {
+      match (PriceConf { price: self.price, conf: 0, expo: self.expo }).scale_to_exponent(target_expo) {
+        Some(x) => x.price,
+        None if scaling_up && self.price < 0 => i64::MIN,
+        None if scaling_up => i64::MAX,
+        None => 0,
+      }
+    };
+    let conf = if self.conf == 0 {
+      0
+    } else {
+      match (PriceConf { price: 0, conf: self.conf, expo: self.expo }).scale_to_exponent(target_expo) {
+        Some(x) => x.conf,
+        None if scaling_up => u64::MAX,
+        // The confidence is rounded up, as in `scale_to_exponent`.
+        None => 1,
+      }
+    };
+    PriceConf { price, conf, expo: target_expo }
+  }
+
+  /**
+   * Get the lower bound of the confidence interval, `price - conf`, as a `PriceConf` with zero
+   * confidence and the same exponent. Returns `None` if the bound does not fit into an i64.
+   */
+  pub fn lower_bound(&self) -> Option<PriceConf> {
+    let price = (self.price as i128).checked_sub(self.conf as i128)?;
+    if price < i64::MIN as i128 {
+      return None;
+    }
+    Some(PriceConf { price: price as i64, conf: 0, expo: self.expo })
+  }
+
+  /**
+   * Get the upper bound of the confidence interval, `price + conf`, as a `PriceConf` with zero
+   * confidence and the same exponent. Returns `None` if the bound does not fit into an i64.
+   */
+  pub fn upper_bound(&self) -> Option<PriceConf> {
+    let price = (self.price as i128).checked_add(self.conf as i128)?;
+    if price > i64::MAX as i128 {
+      return None;
+    }
+    Some(PriceConf { price: price as i64, conf: 0, expo: self.expo })
+  }
+
+  /**
+   * Get the confidence interval as a fraction of the price, in basis points (i.e., 1/100th of a
+   * percent), rounded down. Returns `None` if the price is zero or the ratio does not fit in a u64.
+   */
+  pub fn get_confidence_ratio_bps(&self) -> Option<u64> {
+    let (price, _) = PriceConf::to_unsigned(self.price);
+    // conf * 10000 uses at most 64 + 14 bits
+    let ratio = (self.conf as u128).checked_mul(10_000)?.checked_div(price as u128)?;
+    if ratio > u64::MAX as u128 {
+      return None;
+    }
+    Some(ratio as u64)
+  }
+
+  /**
+   * Get the change from `reference` to this price in basis points of the reference price:
+   * `(self - reference) * 10000 / |reference|`, rounded toward zero. For example, a price of
+   * 101.5 has changed by 150 basis points from a reference price of 100. Only the prices are
+   * used; both confidences are ignored. The exponents may differ.
+   *
+   * Returns `None` if the reference price is zero, or the exponents are too far apart or the
+   * result is too large to be represented.
+   */
+  pub fn percent_change_bps(&self, reference: &PriceConf) -> Option<i64> {
+    if reference.price == 0 {
+      return None;
+    }
+
+    // Align both prices to the smaller exponent using 128-bit integers.
+    let expo = self.expo.min(reference.expo);
+    let scale = |x: &PriceConf| -> Option<i128> {
+      let shift = x.expo.checked_sub(expo)?;
+      (x.price as i128).checked_mul(10i128.checked_pow(shift as u32)?)
+    };
+    let price = if self.price == 0 { 0 } else { scale(self)? };
+    let reference = scale(reference)?;
+
+    let change = price.checked_sub(reference)?.checked_mul(10_000)?;
+    let bps = change / reference.unsigned_abs() as i128;
+    if bps < i64::MIN as i128 || bps > i64::MAX as i128 {
+      return None;
+    }
+    Some(bps as i64)
+  }
+
+  /**
+   * Get the number of ticks of size `tick_size` closest to this price, i.e., `self / tick_size`
+   * rounded to the nearest integer (with ties rounded away from zero). The exponents of the price
+   * and the tick size may differ. Only the prices are used; both confidences are ignored.
+   *
+   * Returns `None` if the tick size is zero or negative, or the number of ticks does not fit into
+   * an i64.
+   */
+  pub fn to_ticks(&self, tick_size: &PriceConf) -> Option<i64> {
+    if tick_size.price <= 0 {
+      return None;
+    }
+    let (price, sign) = PriceConf::to_unsigned(self.price);
+    if price == 0 {
+      return Some(0);
+    }
+
+    // Align the exponents by scaling up either the price or the tick size.
+    let shift = self.expo.checked_sub(tick_size.expo)?;
+    let (numerator, denominator) = if shift >= 0 {
+      let scale = 10u128.checked_pow(shift as u32)?;
+      ((price as u128).checked_mul(scale)?, tick_size.price as u128)
+    } else {
+      match 10u128.checked_pow(shift.unsigned_abs()) {
+        Some(scale) => match (tick_size.price as u128).checked_mul(scale) {
+          Some(denominator) => (price as u128, denominator),
+          // The tick size exceeds 2^64 times the price, so it rounds to 0 ticks.
+          None => return Some(0),
+        },
+        None => return Some(0),
+      }
+    };
+
+    let ticks = PriceConf::div_nearest(numerator, denominator)?;
+    if sign < 0 {
+      if ticks > i64::MAX as u128 + 1 {
+        return None;
+      }
+      Some((ticks as i128).wrapping_neg() as i64)
+    } else {
+      if ticks > i64::MAX as u128 {
+        return None;
+      }
+      Some(ticks as i64)
+    }
+  }
+
+  /**
+   * Get the price as an i128 multiple of `10^target_expo`, without the confidence. This is a
+   * compact fixed-point representation for passing a price to another program when both sides
+   * agree on the exponent. The price is rounded to nearest (with ties rounded away from zero), as
+   * in `scale_to_exponent`.
+   *
+   * Returns `None` if the result overflows an i128, or if a nonzero price would round to 0.
+   */
+  pub fn to_i128_scaled(&self, target_expo: i32) -> Option<i128> {
+    if self.price == 0 {
+      return Some(0);
+    }
+    let delta = target_expo.checked_sub(self.expo)?;
+    if delta <= 0 {
+      let scale = 10i128.checked_pow(delta.unsigned_abs())?;
+      return (self.price as i128).checked_mul(scale);
+    }
+
+    let (price, sign) = PriceConf::to_unsigned(self.price);
+    // A scale that overflows would round every price to 0.
+    let scaled = PriceConf::div_nearest(price as u128, 10u128.checked_pow(delta as u32)?)?;
+    if scaled == 0 {
+      return None;
+    }
+    Some(scaled as i128 * sign as i128)
+  }
+
+  /**
+   * Get the price as an i64 multiple of `10^scale_expo`, without the confidence, e.g., in units of
+   * 10^-4 for a contract that stores prices in basis points. The price is rounded to nearest (with
+   * ties rounded away from zero), as in `scale_to_exponent`. Unlike `scale_to_exponent`, this
+   * cannot fail because the confidence is out of range.
+   *
+   * Returns `None` if the result does not fit into an i64, or if a nonzero price would round to 0.
+   */
+  pub fn to_scaled_integer(&self, scale_expo: i32) -> Option<i64> {
+    let price = self.to_i128_scaled(scale_expo)?;
+    if price < i64::MIN as i128 || price > i64::MAX as i128 {
+      return None;
+    }
+    Some(price as i64)
+  }
+
+  /**
+   * Get the exact decimal representation of the price, e.g., "-123.45" for a price of -12345 with
+   * exponent -2. Unlike `to_f64`, this does not lose precision. Trailing zeros implied by the
+   * exponent are written out, e.g., "12300" for a price of 123 with exponent 2.
+   */
+  pub fn to_decimal_string(&self) -> String {
+    let (price, sign) = PriceConf::to_unsigned(self.price);
+    let mut s = String::new();
+    // Writing to a String cannot fail.
+    let _ = write_fixed_point(&mut s, sign < 0, price, self.expo);
+    s
+  }
+
+  /** Get the exact decimal representation of the confidence interval. See `to_decimal_string`. */
+  pub fn conf_to_decimal_string(&self) -> String {
+    let mut s = String::new();
+    let _ = write_fixed_point(&mut s, false, self.conf, self.expo);
+    s
+  }
+
+  /**
+   * Get the price as a floating-point number, i.e., `price * 10^expo`. The result may lose
+   * precision, so this method is intended for off-chain display and analytics only.
+   */
+  #[cfg(feature = "std")]
+  pub fn to_f64(&self) -> f64 {
+    self.price as f64 * 10f64.powi(self.expo)
+  }
+
+  /** Get the confidence interval as a floating-point number, i.e., `conf * 10^expo`. */
+  #[cfg(feature = "std")]
+  pub fn conf_to_f64(&self) -> f64 {
+    self.conf as f64 * 10f64.powi(self.expo)
+  }
+
+  /**
+   * Convert a floating-point price and confidence into a `PriceConf` with exponent `expo`,
+   * rounding both to the nearest representable value. Returns `None` if either input is not
+   * finite, if `conf` is negative, or if the scaled values do not fit into the fixed-point
+   * representation.
+   */
+  #[cfg(feature = "std")]
+  pub fn from_f64(value: f64, conf: f64, expo: i32) -> Option<PriceConf> {
+    if !value.is_finite() || !conf.is_finite() {
+      return None;
+    }
+
+    let scale = 10f64.powi(expo.checked_neg()?);
+    let price = (value * scale).round();
+    let conf = (conf * scale).round();
+
+    // i64::MAX and u64::MAX round up to 2^63 and 2^64 respectively, so these bounds are exclusive.
+    if price < i64::MIN as f64 || price >= i64::MAX as f64 || conf < 0.0 || conf >= u64::MAX as f64 {
+      return None;
+    }
+
+    Some(PriceConf {
+      price: price as i64,
+      conf: conf as u64,
+      expo,
+    })
+  }
+
+  /**
+   * Helper function to find the first element of `prices` that compares as `direction` to all
+   * elements before it, i.e., the maximum for `Ordering::Greater` and the minimum for
+   * `Ordering::Less`.
+   */
+  fn extreme_of(prices: &[PriceConf], direction: Ordering) -> Option<PriceConf> {
+    let expo = prices.iter().map(|price| price.expo).min()?;
+    let mut best: Option<(i64, PriceConf)> = None;
+    for price in prices {
+      let aligned = PriceConf::price_at_lower_exponent(price.price, price.expo, expo)?;
+      match best {
+        Some((best_price, _)) if aligned.cmp(&best_price) != direction => {}
+        _ => best = Some((aligned, *price)),
+      }
+    }
+    best.map(|(_, price)| price)
+  }
+
+  /**
+   * Helper function to express `price * 10^expo` with the smaller exponent `target_expo`.
+   * Returns `None` if the result does not fit in an i64.
+   */
+  fn price_at_lower_exponent(price: i64, expo: i32, target_expo: i32) -> Option<i64> {
+    let mut delta = expo.checked_sub(target_expo)?;
+    let mut p = price;
+    // 2nd term is a short-circuit to bound op consumption
+    while delta > 0 && p != 0 {
+      p = p.checked_mul(10)?;
+      delta -= 1;
+    }
+    Some(p)
+  }
+
+  /**
+   * Helper function to convert a 128-bit price and confidence with exponent `expo` into a
+   * `PriceConf`, dropping least significant digits until both fit into 64 bits. The price is
+   * rounded to nearest and the confidence is rounded up, as in `scale_to_exponent`.
+   */
+  fn from_wide(price: i128, conf: u128, expo: i32) -> Option<PriceConf> {
+    let out_of_range = |p: i128, c: u128| p > i64::MAX as i128 || p < i64::MIN as i128 || c > u64::MAX as u128;
+    let mut p = price;
+    let mut c = conf;
+    let mut e = expo;
+    // Rounding up can carry the result out of range again, in which case another digit is dropped.
+    while out_of_range(p, c) {
+      // Count the digits to drop, so that they are dropped with a single rounding step.
+      let mut digits = 0;
+      let (mut short_p, mut short_c) = (p, c);
+      while out_of_range(short_p, short_c) {
+        short_p /= 10;
+        short_c /= 10;
+        digits += 1;
+      }
+
+      let (rounded_p, rounded_c) = PriceConf::drop_digits(p, c, digits, Rounding::Nearest)?;
+      p = rounded_p;
+      c = rounded_c;
+      e = e.checked_add(digits)?;
+    }
+
+    Some(PriceConf {
+      price: p as i64,
+      conf: c as u64,
+      expo: e,
+    })
+  }
+
+  /**
+   * Helper function to express a 128-bit price and confidence with exponent `expo` as a
+   * `PriceConf` with exponent `target_expo`. The price is rounded to nearest and the confidence is
+   * rounded up, as in `scale_to_exponent`. Returns `None` if the result does not fit into 64 bits.
+   */
+  fn wide_to_exponent(price: i128, conf: u128, expo: i32, target_expo: i32) -> Option<PriceConf> {
+    let mut delta = target_expo.checked_sub(expo)?;
+    let mut p = price;
+    let mut c = conf;
+    if delta >= 0 {
+      let (rounded_p, rounded_c) = PriceConf::drop_digits(price, conf, delta, Rounding::Nearest)?;
+      p = rounded_p;
+      c = rounded_c;
+      if p == 0 && price != 0 {
+        return None;
+      }
+    } else {
+      // 2nd term is a short-circuit to bound op consumption
+      while delta < 0 && (p != 0 || c != 0) {
+        p = p.checked_mul(10)?;
+        c = c.checked_mul(10)?;
+        delta = delta.checked_add(1)?;
+      }
+    }
+
+    if p > i64::MAX as i128 || p < i64::MIN as i128 || c > u64::MAX as u128 {
+      return None;
+    }
+
+    Some(PriceConf {
+      price: p as i64,
+      conf: c as u64,
+      expo: target_expo,
+    })
+  }
+
+  /**
+   * Helper function to drop the `digits` least significant digits of a 128-bit price and
+   * confidence, as when increasing their exponent by `digits`. The price is rounded according to
+   * `rounding` and the confidence is rounded up, as in `scale_to_exponent_with`. The rounding step
+   * cannot overflow unless `digits` is 0, in which case nothing is rounded.
+   */
+  fn drop_digits(price: i128, conf: u128, digits: i32, rounding: Rounding) -> Option<(i128, u128)> {
+    let mut p = price;
+    let mut c = conf;
+    let mut digits = digits;
+    // The most significant digit dropped from the price, whether any nonzero digit has been
+    // dropped from the price, and likewise for the confidence. These determine the rounding of
+    // the result.
+    let mut p_digit = 0;
+    let mut p_inexact = false;
+    let mut c_inexact = false;
+    // 2nd term is a short-circuit to bound op consumption
+    while digits > 0 && (p != 0 || c != 0) {
+      p_digit = p.checked_rem(10)?;
+      p_inexact |= p_digit != 0;
+      c_inexact |= c.checked_rem(10)? != 0;
+      p = p.checked_div(10)?;
+      c = c.checked_div(10)?;
+      digits = digits.checked_sub(1)?;
+    }
+    if digits > 0 {
+      // Exited early: every remaining digit to drop is zero.
+      p_digit = 0;
+    }
+
+    // Dropping digits truncates toward zero, so only rounding away from zero needs a step.
+    let away_from_zero = match rounding {
+      Rounding::Truncate => false,
+      Rounding::Nearest => p_digit >= 5 || p_digit <= -5,
+      Rounding::Ceil => p_inexact && price > 0,
+      Rounding::Floor => p_inexact && price < 0,
+    };
+    if away_from_zero {
+      p = if price > 0 { p.checked_add(1)? } else { p.checked_sub(1)? };
+    }
+    if c_inexact {
+      c = c.checked_add(1)?;
+    }
+    Some((p, c))
+  }
+
+  /**
+   * Helper function to express `x` exactly with the smaller exponent `target_expo` using 128-bit
+   * integers. Returns `None` if the result does not fit.
+   */
+  fn to_wide_at_exponent(x: &PriceConf, target_expo: i32) -> Option<(i128, u128)> {
+    // The difference is non-negative, as `target_expo` is at most `x.expo`.
+    let scale = 10i128.checked_pow(x.expo.checked_sub(target_expo)? as u32)?;
+    Some((
+      (x.price as i128).checked_mul(scale)?,
+      (x.conf as u128).checked_mul(scale as u128)?,
+    ))
+  }
+
+  /**
+   * Helper function to compute `numerator / denominator` rounded to the nearest integer, with ties
+   * rounded up.
+   */
+  fn div_nearest(numerator: u128, denominator: u128) -> Option<u128> {
+    let quotient = numerator.checked_div(denominator)?;
+    let remainder = numerator.checked_rem(denominator)?;
+    // Equivalent to 2 * remainder >= denominator, without the risk of overflow.
+    if remainder >= denominator - remainder {
+      quotient.checked_add(1)
+    } else {
+      Some(quotient)
+    }
+  }
+
+  /**
+   * Helper function to compute `log2(x)` for a nonzero `x`, with LOG_FRAC_BITS fractional bits
+   * (rounded down). The fractional bits are found one at a time by repeatedly squaring the
+   * mantissa.
+   */
+  fn log2_fixed(x: u64) -> i128 {
+    let integer = 63 - x.leading_zeros();
+    // The mantissa x / 2^integer in [1, 2), with LOG_FRAC_BITS fractional bits
+    let mut mantissa = ((x as u128) << LOG_FRAC_BITS) >> integer;
+    let mut fraction: u128 = 0;
+    for bit in (0..LOG_FRAC_BITS).rev() {
+      // The square is below 2^(2 * LOG_FRAC_BITS + 2), which fits into a u128.
+      mantissa = (mantissa * mantissa) >> LOG_FRAC_BITS;
+      if mantissa >= 2 * LOG_ONE {
+        fraction |= 1 << bit;
+        mantissa >>= 1;
+      }
+    }
+    ((integer as i128) << LOG_FRAC_BITS) + fraction as i128
+  }
+
+  /**
+   * Helper function to compute `2^x` rounded to the nearest integer, where `x` has LOG_FRAC_BITS
+   * fractional bits. Returns `None` if the result does not fit into a u128.
+   */
+  fn exp2_fixed(x: i128) -> Option<u128> {
+    let integer = x >> LOG_FRAC_BITS;
+    let fraction = (x & (LOG_ONE as i128 - 1)) as u128;
+
+    // 2^fraction = e^(fraction * ln 2), summing the Taylor series until its terms vanish. The sum
+    // is below 2, so it fits into LOG_FRAC_BITS + 1 bits.
+    let exponent = (fraction * LN_2) >> LOG_FRAC_BITS;
+    let mut sum = LOG_ONE;
+    let mut term = LOG_ONE;
+    let mut k = 1;
+    while term > 0 {
+      term = ((term * exponent) >> LOG_FRAC_BITS) / k;
+      sum += term;
+      k += 1;
+    }
+
+    // sum * 2^integer / 2^LOG_FRAC_BITS, rounded to nearest
+    let shift = (LOG_FRAC_BITS as i128).checked_sub(integer)?;
+    if shift <= 0 {
+      // The sum has LOG_FRAC_BITS + 1 bits, so it can be shifted left by at most 127 - that.
+      if -shift > (127 - LOG_FRAC_BITS) as i128 {
+        return None;
+      }
+      Some(sum << -shift)
+    } else if shift >= 128 {
+      Some(0)
+    } else {
+      Some((sum + (1 << (shift - 1))) >> shift)
+    }
+  }
+
+  /** Helper function to compute `floor(sqrt(x))` using Newton's method. */
+  fn isqrt(x: u128) -> u128 {
+    if x < 2 {
+      return x;
+    }
+    // Start above the root, from which the iteration decreases monotonically toward it.
+    let mut root = 1u128 << (64 - x.leading_zeros() / 2);
+    loop {
+      let next = (root + x / root) / 2;
+      if next >= root {
+        return root;
+      }
+      root = next;
+    }
+  }
+
+  /** Helper function to compute `ceil(numerator / denominator)`. */
+  fn ceil_div(numerator: u128, denominator: u128) -> Option<u128> {
+    let quotient = numerator.checked_div(denominator)?;
+    if numerator.checked_rem(denominator)? != 0 {
+      quotient.checked_add(1)
+    } else {
+      Some(quotient)
+    }
+  }
+
+  /**
+   * Helper function to convert signed integers to unsigned and a sign bit, which simplifies
+   * some of the computations above.
+   */
+  fn to_unsigned(x: i64) -> (u64, i64) {
+    if x == i64::MIN {
+      // special case because i64::MIN == -i64::MIN
+      (i64::MAX as u64 + 1, -1)
+    } else if x < 0 {
+      (-x as u64, -1)
+    } else {
+      (x as u64, 1)
+    }
+  }
+}
+
+/*
+ * Operator sugar for the common cases where the arithmetic cannot fail in practice, e.g.,
+ * `a * b` instead of `a.mul(&b).unwrap()`. Each operator delegates to the method of the same
+ * name and panics if it returns `None`, so on-chain programs that must handle failures should keep
+ * calling the methods, which remain the primary API. `+` and `-` additionally require both
+ * operands to have the same exponent, as the methods do.
+ *
+ * The operator traits are deliberately not imported in this module, so that calls such as
+ * `x.mul(&y)` keep resolving to the inherent methods.
+ */
+impl core::ops::Add for PriceConf {
+  type Output = PriceConf;
+
+  fn add(self, other: PriceConf) -> PriceConf {
+    PriceConf::add(&self, &other).expect("PriceConf addition overflowed")
+  }
+}
+
+impl core::ops::Add<&PriceConf> for &PriceConf {
+  type Output = PriceConf;
+
+  fn add(self, other: &PriceConf) -> PriceConf {
+    *self + *other
+  }
+}
+
+impl core::ops::Sub for PriceConf {
+  type Output = PriceConf;
+
+  fn sub(self, other: PriceConf) -> PriceConf {
+    PriceConf::sub(&self, &other).expect("PriceConf subtraction overflowed")
+  }
+}
+
+impl core::ops::Sub<&PriceConf> for &PriceConf {
+  type Output = PriceConf;
+
+  fn sub(self, other: &PriceConf) -> PriceConf {
+    *self - *other
+  }
+}
+
+impl core::ops::Mul for PriceConf {
+  type Output = PriceConf;
+
+  fn mul(self, other: PriceConf) -> PriceConf {
+    PriceConf::mul(&self, &other).expect("PriceConf multiplication overflowed")
+  }
+}
+
+impl core::ops::Mul<&PriceConf> for &PriceConf {
+  type Output = PriceConf;
+
+  fn mul(self, other: &PriceConf) -> PriceConf {
+    *self * *other
+  }
+}
+
+impl core::ops::Div for PriceConf {
+  type Output = PriceConf;
+
+  fn div(self, other: PriceConf) -> PriceConf {
+    PriceConf::div(&self, &other).expect("PriceConf division failed (division by zero or overflow)")
+  }
+}
+
+impl core::ops::Div<&PriceConf> for &PriceConf {
+  type Output = PriceConf;
+
+  fn div(self, other: &PriceConf) -> PriceConf {
+    *self / *other
   }
+}
 
-  /**
-   * Helper function to convert signed integers to unsigned and a sign bit, which simplifies
-   * some of the computations above.
-   */
-  fn to_unsigned(x: i64) -> (u64, i64) {
-    if x == i64::MIN {
-      // special case because i64::MIN == -i64::MIN
-      (i64::MAX as u64 + 1, -1)
-    } else if x < 0 {
-      (-x as u64, -1)
+/**
+ * Formats the price and confidence as decimal numbers, e.g.,
+ * `PriceConf { price: 12345, conf: 267, expo: -2 }` is displayed as `123.45 ± 2.67`.
+ */
+impl fmt::Display for PriceConf {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let (price, sign) = PriceConf::to_unsigned(self.price);
+    write_fixed_point(f, sign < 0, price, self.expo)?;
+    write!(f, " ± ")?;
+    write_fixed_point(f, false, self.conf, self.expo)
+  }
+}
+
+/** Write the decimal representation of `(-1 if negative) * value * 10^expo`. */
+fn write_fixed_point(f: &mut impl fmt::Write, negative: bool, value: u64, expo: i32) -> fmt::Result {
+  if negative {
+    f.write_char('-')?;
+  }
+  let digits = value.to_string();
+  if expo >= 0 {
+    f.write_str(&digits)?;
+    if value != 0 {
+      for _ in 0..expo {
+        f.write_char('0')?;
+      }
+    }
+    Ok(())
+  } else {
+    let frac_digits = expo.unsigned_abs() as usize;
+    if digits.len() > frac_digits {
+      let (int_part, frac_part) = digits.split_at(digits.len() - frac_digits);
+      write!(f, "{}.{}", int_part, frac_part)
     } else {
-      (x as u64, 1)
+      write!(f, "0.{:0>width$}", digits, width = frac_digits)
     }
   }
 }
 
 #[cfg(test)]
 mod test {
-  use crate::price_conf::{MAX_PD_V_U64, PD_EXPO, PD_SCALE, PriceConf};
-
-  const MAX_PD_V_I64: i64 = MAX_PD_V_U64 as i64;
-  const MIN_PD_V_I64: i64 = -MAX_PD_V_I64;
+  use crate::price_conf::{MAX_PD_V_I64, MAX_PD_V_U64, MIN_PD_V_I64, PD_EXPO, PD_SCALE, PriceConf, Rounding};
+  use crate::PythError;
 
   fn pc(price: i64, conf: u64, expo: i32) -> PriceConf {
     PriceConf {
@@ -259,6 +1530,20 @@ mod test {
     }.scale_to_exponent(expo).unwrap()
   }
 
+  #[test]
+  fn test_pd_constants() {
+    assert_eq!(PD_SCALE, 10u64.pow(-PD_EXPO as u32));
+    // The assumptions of mul and div about normalized values
+    assert!(MAX_PD_V_U64.checked_mul(MAX_PD_V_U64).is_some());
+    assert!(MAX_PD_V_U64.checked_mul(PD_SCALE).is_some());
+
+    for x in [pc(i64::MAX, u64::MAX, 0), pc(i64::MIN, 1, 0), pc(MIN_PD_V_I64 - 1, 0, 0)] {
+      let normalized = x.normalize().unwrap();
+      assert!(MIN_PD_V_I64 <= normalized.price && normalized.price <= MAX_PD_V_I64);
+      assert!(normalized.conf <= MAX_PD_V_U64);
+    }
+  }
+
   #[test]
   fn test_normalize() {
     fn succeeds(
@@ -301,6 +1586,95 @@ mod test {
     fails(pc(1, u64::MAX, i32::MAX - expo + 1));
   }
 
+  #[test]
+  fn test_normalize_keeping_precision() {
+    // Inputs in range are returned unchanged, whatever their number of digits.
+    assert_eq!(pc(5, 1, -2).normalize_keeping_precision(3), Some(pc(5, 1, -2)));
+    assert_eq!(pc(MAX_PD_V_I64, 1, 0).normalize_keeping_precision(20), Some(pc(MAX_PD_V_I64, 1, 0)));
+
+    // A large price keeps 9 digits when normalized.
+    let x = pc(123_456_789_012, 10, -4);
+    assert_eq!(x.normalize_keeping_precision(9), Some(pc(123_456_789, 0, -1)));
+    assert_eq!(x.normalize_keeping_precision(9), x.normalize());
+    assert_eq!(x.normalize_keeping_precision(10), None);
+
+    // A wide confidence forces the price down to few digits.
+    let x = pc(12_345, 100_000_000_000, -2);
+    assert_eq!(x.normalize(), Some(pc(12, 100_000_000, 1)));
+    assert_eq!(x.normalize_keeping_precision(2), Some(pc(12, 100_000_000, 1)));
+    assert_eq!(x.normalize_keeping_precision(3), None);
+    let x = pc(-5, 1_000_000_000_000, 0);
+    assert_eq!(x.normalize(), Some(pc(0, 100_000_000, 4)));
+    assert_eq!(x.normalize_keeping_precision(1), None);
+    assert_eq!(x.normalize_keeping_precision(0), Some(pc(0, 100_000_000, 4)));
+
+    // A zero price has no precision to lose.
+    assert_eq!(pc(0, u64::MAX, 0).normalize_keeping_precision(9), pc(0, u64::MAX, 0).normalize());
+    assert_eq!(pc(1, 0, i32::MAX).normalize_keeping_precision(0), Some(pc(1, 0, i32::MAX)));
+    assert_eq!(pc(i64::MAX, 0, i32::MAX).normalize_keeping_precision(0), None);
+  }
+
+  #[test]
+  fn test_try_normalize() {
+    assert_eq!(pc(1, 1, 0).try_normalize(), Ok(pc(1, 1, 0)));
+    assert_eq!(pc(i64::MAX, 1, i32::MAX - 11).try_normalize(), Ok(pc(92_233_720, 0, i32::MAX)));
+    assert_eq!(pc(i64::MAX, 1, i32::MAX - 10).try_normalize(), Err(PythError::ExponentOverflow));
+    assert_eq!(pc(1, u64::MAX, i32::MAX).try_normalize(), Err(PythError::ExponentOverflow));
+    // Only the exponent can overflow, so normalize returns None in exactly these cases.
+    assert_eq!(pc(i64::MAX, 1, i32::MAX - 10).normalize(), None);
+  }
+
+  #[test]
+  fn test_to_exponent_normalized() {
+    fn two_calls(x: PriceConf, expo: i32) -> Option<PriceConf> {
+      x.normalize()?.scale_to_exponent(expo)
+    }
+
+    // When normalizing is exact, both agree.
+    for (x, expo) in [
+      (pc(12345, 267, -2), -2),
+      (pc(12345, 267, -2), -6),
+      (pc(12345, 267, -2), 0),
+      (pc(-12345, 267, -2), 1),
+      (pc(1_000_000_000, 10, -9), -1),
+      (pc(0, 0, 5), -100),
+    ] {
+      assert_eq!(x.to_exponent_normalized(expo), two_calls(x, expo));
+    }
+
+    // Normalizing truncates a digit, which is then lost: the price should round up, and the
+    // confidence must round up.
+    let x = pc(2_684_354_559, 2_684_354_559, -9);
+    assert_eq!(two_calls(x, -8), Some(pc(268_435_455, 268_435_455, -8)));
+    assert_eq!(x.to_exponent_normalized(-8), Some(pc(268_435_456, 268_435_456, -8)));
+    assert_eq!(x.to_exponent_normalized(-8), x.scale_to_exponent(-8));
+    // Normalizing drops digits that are needed at a smaller exponent.
+    assert_eq!(two_calls(x, -9), Some(pc(2_684_354_550, 2_684_354_550, -9)));
+    assert_eq!(x.to_exponent_normalized(-9), Some(x));
+
+    // Scaling up in one multiplication.
+    assert_eq!(pc(-1, 1, 0).to_exponent_normalized(-18), Some(pc(-1_000_000_000_000_000_000, 1_000_000_000_000_000_000, -18)));
+    assert_eq!(pc(0, 1, 0).to_exponent_normalized(-19), Some(pc(0, 10_000_000_000_000_000_000, -19)));
+    assert_eq!(pc(1, 0, 0).to_exponent_normalized(-19), None);
+    assert_eq!(pc(0, 1, 0).to_exponent_normalized(-20), None);
+    assert_eq!(pc(0, 0, i32::MAX).to_exponent_normalized(i32::MIN), None);
+    assert_eq!(pc(0, 0, 0).to_exponent_normalized(i32::MIN), Some(pc(0, 0, i32::MIN)));
+    assert_eq!(pc(i64::MAX, 0, 0).to_exponent_normalized(-1), None);
+    // Scaling down to an exponent where the price would round to 0.
+    assert_eq!(pc(1, 1, 0).to_exponent_normalized(1), None);
+  }
+
+  #[test]
+  fn test_try_new() {
+    assert_eq!(PriceConf::try_new(12345, 267, -2), Ok(pc(12345, 267, -2)));
+    assert_eq!(PriceConf::try_new(i64::MIN, u64::MAX, i32::MIN), Ok(pc(i64::MIN, u64::MAX, i32::MIN)));
+    // Already normalized values are accepted at any exponent.
+    assert_eq!(PriceConf::try_new(1, 1, i32::MAX), Ok(pc(1, 1, i32::MAX)));
+
+    assert_eq!(PriceConf::try_new(i64::MAX, 1, i32::MAX - 10), Err(PythError::ExponentOverflow));
+    assert_eq!(PriceConf::try_new(1, u64::MAX, i32::MAX), Err(PythError::ExponentOverflow));
+  }
+
   #[test]
   fn test_scale_to_exponent() {
     fn succeeds(
@@ -319,10 +1693,12 @@ mod test {
     }
 
     succeeds(pc(1234, 1234, 0), 0, pc(1234, 1234, 0));
-    succeeds(pc(1234, 1234, 0), 1, pc(123, 123, 1));
-    succeeds(pc(1234, 1234, 0), 2, pc(12, 12, 2));
-    succeeds(pc(-1234, 1234, 0), 2, pc(-12, 12, 2));
-    succeeds(pc(1234, 1234, 0), 4, pc(0, 0, 4));
+    succeeds(pc(1234, 1234, 0), 1, pc(123, 124, 1));
+    succeeds(pc(1234, 1234, 0), 2, pc(12, 13, 2));
+    succeeds(pc(-1234, 1234, 0), 2, pc(-12, 13, 2));
+    succeeds(pc(1234, 1234, 0), 3, pc(1, 2, 3));
+    succeeds(pc(1234, 1200, 0), 2, pc(12, 12, 2));
+    succeeds(pc(0, 0, 0), 1000, pc(0, 0, 1000));
     succeeds(pc(1234, 1234, 0), -1, pc(12340, 12340, -1));
     succeeds(pc(1234, 1234, 0), -2, pc(123400, 123400, -2));
     succeeds(pc(1234, 1234, 0), -8, pc(123400000000, 123400000000, -8));
@@ -330,11 +1706,135 @@ mod test {
     fails(pc(1234, 1234, 0), -20);
     fails(pc(1234, 0, 0), -20);
     fails(pc(0, 1234, 0), -20);
+    // a nonzero price would round to zero in this exponent
+    fails(pc(1234, 1234, 0), 4);
+    fails(pc(1234, 0, 0), 1000);
+    fails(pc(-499, 0, 0), 3);
 
     // fails because exponent delta overflows
     fails(pc(1, 1, i32::MIN), i32::MAX);
   }
 
+  #[test]
+  fn test_scale_to_exponent_rounding() {
+    fn succeeds(
+      price1: PriceConf,
+      target: i32,
+      expected: PriceConf,
+    ) {
+      assert_eq!(price1.scale_to_exponent(target).unwrap(), expected);
+    }
+
+    // The price rounds to nearest, with ties away from zero.
+    succeeds(pc(19, 0, 0), 1, pc(2, 0, 1));
+    succeeds(pc(15, 0, 0), 1, pc(2, 0, 1));
+    succeeds(pc(14, 0, 0), 1, pc(1, 0, 1));
+    succeeds(pc(-15, 0, 0), 1, pc(-2, 0, 1));
+    succeeds(pc(-14, 0, 0), 1, pc(-1, 0, 1));
+    succeeds(pc(-19, 0, 0), 1, pc(-2, 0, 1));
+    succeeds(pc(5, 0, 0), 1, pc(1, 0, 1));
+    succeeds(pc(-5, 0, 0), 1, pc(-1, 0, 1));
+    assert_eq!(pc(5, 0, 0).scale_to_exponent(2), None);
+    // Rounding happens once, not at every digit.
+    succeeds(pc(149, 0, 0), 2, pc(1, 0, 2));
+    succeeds(pc(-149, 0, 0), 2, pc(-1, 0, 2));
+    succeeds(pc(150, 0, 0), 2, pc(2, 0, 2));
+    succeeds(pc(-150, 0, 0), 2, pc(-2, 0, 2));
+    succeeds(pc(i64::MAX, 0, 0), 1, pc(i64::MAX / 10 + 1, 0, 1));
+    succeeds(pc(i64::MIN, 0, 0), 1, pc(i64::MIN / 10 - 1, 0, 1));
+
+    // The confidence always rounds up.
+    succeeds(pc(0, 10, 0), 1, pc(0, 1, 1));
+    succeeds(pc(0, 11, 0), 1, pc(0, 2, 1));
+    succeeds(pc(0, 15, 0), 1, pc(0, 2, 1));
+    succeeds(pc(0, 101, 0), 2, pc(0, 2, 2));
+    succeeds(pc(0, 1, 0), 5, pc(0, 1, 5));
+    succeeds(pc(0, u64::MAX, 0), 1, pc(0, u64::MAX / 10 + 1, 1));
+  }
+
+  #[test]
+  fn test_scale_to_exponent_with() {
+    use Rounding::{Ceil, Floor, Nearest, Truncate};
+
+    fn succeeds(price1: PriceConf, target: i32, rounding: Rounding, expected: PriceConf) {
+      assert_eq!(price1.scale_to_exponent_with(target, rounding).unwrap(), expected);
+    }
+
+    // 12.34 and -12.34 with a nonzero remainder of 0.34 or 0.04
+    for (rounding, pos, neg) in [(Truncate, 12, -12), (Nearest, 12, -12), (Ceil, 13, -12), (Floor, 12, -13)] {
+      succeeds(pc(1234, 0, -2), 0, rounding, pc(pos, 0, 0));
+      succeeds(pc(-1234, 0, -2), 0, rounding, pc(neg, 0, 0));
+    }
+    for (rounding, pos, neg) in [(Truncate, 123, -123), (Nearest, 123, -123), (Ceil, 124, -123), (Floor, 123, -124)] {
+      succeeds(pc(1234, 0, -2), -1, rounding, pc(pos, 0, -1));
+      succeeds(pc(-1234, 0, -2), -1, rounding, pc(neg, 0, -1));
+    }
+    // 12.56 and -12.56
+    for (rounding, pos, neg) in [(Truncate, 12, -12), (Nearest, 13, -13), (Ceil, 13, -12), (Floor, 12, -13)] {
+      succeeds(pc(1256, 0, -2), 0, rounding, pc(pos, 0, 0));
+      succeeds(pc(-1256, 0, -2), 0, rounding, pc(neg, 0, 0));
+    }
+    // A nonzero digit below the most significant dropped digit
+    succeeds(pc(1201, 0, -2), 0, Ceil, pc(13, 0, 0));
+    succeeds(pc(-1201, 0, -2), 0, Floor, pc(-13, 0, 0));
+    succeeds(pc(1201, 0, -2), 0, Nearest, pc(12, 0, 0));
+
+    // Exact results are never rounded.
+    for rounding in [Truncate, Nearest, Ceil, Floor] {
+      succeeds(pc(1200, 0, -2), 0, rounding, pc(12, 0, 0));
+      succeeds(pc(-1200, 0, -2), 0, rounding, pc(-12, 0, 0));
+      succeeds(pc(0, 0, -2), 5, rounding, pc(0, 0, 5));
+      succeeds(pc(12, 3, 0), -2, rounding, pc(1200, 300, -2));
+      assert_eq!(pc(1234, 1, 0).scale_to_exponent_with(0, rounding), Some(pc(1234, 1, 0)));
+    }
+
+    // The confidence always rounds up.
+    for rounding in [Truncate, Nearest, Ceil, Floor] {
+      succeeds(pc(1200, 11, 0), 1, rounding, pc(120, 2, 1));
+    }
+
+    // Prices that would round to zero
+    assert_eq!(pc(4, 0, 0).scale_to_exponent_with(1, Truncate), None);
+    assert_eq!(pc(4, 0, 0).scale_to_exponent_with(1, Floor), None);
+    assert_eq!(pc(-4, 0, 0).scale_to_exponent_with(1, Ceil), None);
+    succeeds(pc(4, 0, 0), 5, Ceil, pc(1, 0, 5));
+    succeeds(pc(-4, 0, 0), 5, Floor, pc(-1, 0, 5));
+
+    // Rounding away from zero at the extremes does not overflow.
+    succeeds(pc(i64::MAX, 0, 0), 1, Ceil, pc(i64::MAX / 10 + 1, 0, 1));
+    succeeds(pc(i64::MIN, 0, 0), 1, Floor, pc(i64::MIN / 10 - 1, 0, 1));
+
+    // scale_to_exponent rounds to nearest.
+    for x in [pc(1234, 5, -2), pc(-1256, 5, -2), pc(i64::MIN, u64::MAX, -2)] {
+      assert_eq!(x.scale_to_exponent(0), x.scale_to_exponent_with(0, Nearest));
+    }
+  }
+
+  #[test]
+  fn test_scale_to_exponent_saturating() {
+    fn succeeds(price1: PriceConf, target: i32) {
+      assert_eq!(price1.scale_to_exponent_saturating(target), price1.scale_to_exponent(target).unwrap());
+    }
+
+    succeeds(pc(1234, 1234, 0), 2);
+    succeeds(pc(-1234, 1234, 0), -8);
+    succeeds(pc(i64::MAX / 10, u64::MAX / 10, 0), -1);
+    succeeds(pc(i64::MIN / 10, 0, 0), -1);
+
+    // Just past the overflow boundary.
+    assert_eq!(pc(i64::MAX / 10 + 1, 1, 0).scale_to_exponent_saturating(-1), pc(i64::MAX, 10, -1));
+    assert_eq!(pc(i64::MIN / 10 - 1, 1, 0).scale_to_exponent_saturating(-1), pc(i64::MIN, 10, -1));
+    assert_eq!(pc(1, u64::MAX / 10 + 1, 0).scale_to_exponent_saturating(-1), pc(10, u64::MAX, -1));
+    assert_eq!(pc(-1, 1, 0).scale_to_exponent_saturating(-30), pc(i64::MIN, u64::MAX, -30));
+    assert_eq!(pc(0, 0, i32::MAX).scale_to_exponent_saturating(i32::MIN), pc(0, 0, i32::MIN));
+    assert_eq!(pc(5, 1, i32::MAX).scale_to_exponent_saturating(i32::MIN), pc(i64::MAX, u64::MAX, i32::MIN));
+
+    // Nonzero prices that are too small become zero.
+    assert_eq!(pc(1234, 1234, 0).scale_to_exponent_saturating(4), pc(0, 1, 4));
+    assert_eq!(pc(-1234, 0, 0).scale_to_exponent_saturating(1000), pc(0, 0, 1000));
+    assert_eq!(pc(1, 1, i32::MIN).scale_to_exponent_saturating(i32::MAX), pc(0, 1, i32::MAX));
+  }
+
   #[test]
   fn test_div() {
     fn succeeds(
@@ -364,6 +1864,12 @@ mod test {
     succeeds(pc(1, 1, 0), pc(-1, 1, 0), pc_scaled(-1, 2, 0, PD_EXPO));
     succeeds(pc(-1, 1, 0), pc(-1, 1, 0), pc_scaled(1, 2, 0, PD_EXPO));
 
+    // The confidence is rounded up when the division is inexact, e.g., (1 +- 1) / 3 has a
+    // confidence of 0.333.. and 1 / (3 +- 1) has a confidence of 1/9 = 0.111..
+    succeeds(pc(1, 1, 0), pc(3, 0, 0), pc(333_333_333, 333_333_334, PD_EXPO));
+    succeeds(pc(1, 0, 0), pc(3, 1, 0), pc(333_333_333, 111_111_112, PD_EXPO));
+    succeeds(pc(1, 1, 0), pc(4, 0, 0), pc(250_000_000, 250_000_000, PD_EXPO));
+
     // Different exponents in the two inputs
     succeeds(pc(100, 10, -8), pc(2, 1, -7), pc_scaled(500_000_000, 300_000_000, -8, PD_EXPO - 1));
     succeeds(pc(100, 10, -4), pc(2, 1, 0), pc_scaled(500_000, 300_000, -8, PD_EXPO + -4));
@@ -373,18 +1879,23 @@ mod test {
     succeeds(pc(MAX_PD_V_I64, MAX_PD_V_U64, 0), pc(1, 1, 0), pc_scaled(MAX_PD_V_I64, 2 * MAX_PD_V_U64, 0, PD_EXPO));
     succeeds(pc(1, 1, 0),
              pc(MAX_PD_V_I64, MAX_PD_V_U64, 0),
-             pc((PD_SCALE as i64) / MAX_PD_V_I64, 2 * (PD_SCALE / MAX_PD_V_U64), PD_EXPO));
+             pc((PD_SCALE as i64) / MAX_PD_V_I64, 2 * (PD_SCALE / MAX_PD_V_U64 + 1), PD_EXPO));
 
     succeeds(pc(MIN_PD_V_I64, MAX_PD_V_U64, 0), pc(MIN_PD_V_I64, MAX_PD_V_U64, 0), pc_scaled(1, 2, 0, PD_EXPO));
     succeeds(pc(MIN_PD_V_I64, MAX_PD_V_U64, 0), pc(1, 1, 0), pc_scaled(MIN_PD_V_I64, 2 * MAX_PD_V_U64, 0, PD_EXPO));
     succeeds(pc(1, 1, 0),
              pc(MIN_PD_V_I64, MAX_PD_V_U64, 0),
-             pc((PD_SCALE as i64) / MIN_PD_V_I64, 2 * (PD_SCALE / MAX_PD_V_U64), PD_EXPO));
+             pc((PD_SCALE as i64) / MIN_PD_V_I64, 2 * (PD_SCALE / MAX_PD_V_U64 + 1), PD_EXPO));
 
     succeeds(pc(1, MAX_PD_V_U64, 0), pc(1, MAX_PD_V_U64, 0), pc_scaled(1, 2 * MAX_PD_V_U64, 0, PD_EXPO));
     // This fails because the confidence interval is too large to be represented in PD_EXPO
     fails(pc(MAX_PD_V_I64, MAX_PD_V_U64, 0), pc(1, MAX_PD_V_U64, 0));
 
+    // The largest normalized inputs produce the largest midprice, which must fit into an i64.
+    succeeds(pc(MAX_PD_V_I64, 0, 0), pc(1, 0, 0), pc_scaled(MAX_PD_V_I64, 0, 0, PD_EXPO));
+    succeeds(pc(MIN_PD_V_I64, 0, 0), pc(-1, 0, 0), pc_scaled(MAX_PD_V_I64, 0, 0, PD_EXPO));
+    succeeds(pc(MAX_PD_V_I64, 0, 0), pc(-1, 0, 0), pc_scaled(MIN_PD_V_I64, 0, 0, PD_EXPO));
+
     // Unnormalized tests below here
 
     // More realistic inputs (get BTC price in ETH)
@@ -392,7 +1903,7 @@ mod test {
     let uten_e7: u64 = 10000000;
     succeeds(pc(520010 * ten_e7, 310 * uten_e7, -8),
              pc(38591 * ten_e7, 18 * uten_e7, -8),
-             pc(1347490347, 1431804, -8));
+             pc(1347490347, 1431807, -8));
 
     // Test with end range of possible inputs to identify overflow
     // These inputs will lose precision due to the initial normalization.
@@ -405,7 +1916,8 @@ mod test {
              pc_scaled(normed.price, 3 * (normed.price as u64), normed.expo, normed.expo + PD_EXPO));
     succeeds(pc(1, 1, 0),
              pc(i64::MAX, u64::MAX, 0),
-             pc((PD_SCALE as i64) / normed.price, 3 * (PD_SCALE / (normed.price as u64)), PD_EXPO - normed.expo));
+             pc((PD_SCALE as i64) / normed.price, (PD_SCALE / (normed.price as u64) + 1) + (2 * PD_SCALE / (normed.price as u64) + 1),
+                PD_EXPO - normed.expo));
 
     succeeds(pc(i64::MAX, 1, 0), pc(i64::MAX, 1, 0), pc_scaled(1, 0, 0, PD_EXPO));
     succeeds(pc(i64::MAX, 1, 0),
@@ -413,7 +1925,7 @@ mod test {
              pc_scaled(normed.price, normed.price as u64, normed.expo, normed.expo + PD_EXPO));
     succeeds(pc(1, 1, 0),
              pc(i64::MAX, 1, 0),
-             pc((PD_SCALE as i64) / normed.price, PD_SCALE / (normed.price as u64), PD_EXPO - normed.expo));
+             pc((PD_SCALE as i64) / normed.price, PD_SCALE / (normed.price as u64) + 1, PD_EXPO - normed.expo));
 
     let normed = pc(i64::MIN, u64::MAX, 0).normalize().unwrap();
     let normed_c = (-normed.price) as u64;
@@ -425,7 +1937,8 @@ mod test {
              pc_scaled(normed.price, 3 * normed_c, normed.expo, normed.expo + PD_EXPO));
     succeeds(pc(1, 1, 0),
              pc(i64::MIN, u64::MAX, 0),
-             pc((PD_SCALE as i64) / normed.price, 3 * (PD_SCALE / normed_c), PD_EXPO - normed.expo));
+             pc((PD_SCALE as i64) / normed.price, (PD_SCALE / normed_c + 1) + (2 * PD_SCALE / normed_c + 1),
+                PD_EXPO - normed.expo));
 
     succeeds(pc(i64::MIN, 1, 0), pc(i64::MIN, 1, 0), pc_scaled(1, 0, 0, PD_EXPO));
     succeeds(pc(i64::MIN, 1, 0),
@@ -433,7 +1946,7 @@ mod test {
              pc_scaled(normed.price, normed_c, normed.expo, normed.expo + PD_EXPO));
     succeeds(pc(1, 1, 0),
              pc(i64::MIN, 1, 0),
-             pc((PD_SCALE as i64) / normed.price, PD_SCALE / (normed_c), PD_EXPO - normed.expo));
+             pc((PD_SCALE as i64) / normed.price, PD_SCALE / (normed_c) + 1, PD_EXPO - normed.expo));
 
     // Price is zero pre-normalization
     succeeds(pc(0, 1, 0), pc(1, 1, 0), pc_scaled(0, 1, 0, PD_EXPO));
@@ -457,6 +1970,29 @@ mod test {
     fails(pc(1, 1, i32::MIN - PD_EXPO), pc(1, 1, 1));
   }
 
+  #[test]
+  fn test_div_floor_and_ceil() {
+    // 1 / 3 = 0.333.., which has a nonzero remainder in PD_EXPO.
+    assert_eq!(pc(1, 1, 0).div(&pc(3, 0, 0)), Some(pc(333_333_333, 333_333_334, PD_EXPO)));
+    assert_eq!(pc(1, 1, 0).div_floor(&pc(3, 0, 0)), Some(pc(333_333_333, 333_333_334, PD_EXPO)));
+    assert_eq!(pc(1, 1, 0).div_ceil(&pc(3, 0, 0)), Some(pc(333_333_334, 333_333_334, PD_EXPO)));
+
+    // Negative quotients round toward negative and positive infinity respectively.
+    assert_eq!(pc(-1, 0, 0).div(&pc(3, 0, 0)), Some(pc(-333_333_333, 0, PD_EXPO)));
+    assert_eq!(pc(-1, 0, 0).div_floor(&pc(3, 0, 0)), Some(pc(-333_333_334, 0, PD_EXPO)));
+    assert_eq!(pc(1, 0, 0).div_ceil(&pc(-3, 0, 0)), Some(pc(-333_333_333, 0, PD_EXPO)));
+
+    // Exact quotients are not rounded.
+    assert_eq!(pc(1, 1, 0).div_floor(&pc(4, 0, 0)), pc(1, 1, 0).div(&pc(4, 0, 0)));
+    assert_eq!(pc(1, 1, 0).div_ceil(&pc(4, 0, 0)), pc(1, 1, 0).div(&pc(4, 0, 0)));
+    assert_eq!(pc(-1, 1, 0).div_ceil(&pc(-4, 0, 0)), Some(pc(250_000_000, 250_000_000, PD_EXPO)));
+
+    // Same failure conditions as div.
+    assert_eq!(pc(1, 0, 0).div_floor(&pc(0, 1, 0)), None);
+    assert_eq!(pc(1, 0, 0).div_ceil(&pc(0, 1, 0)), None);
+    assert_eq!(pc(MAX_PD_V_I64, MAX_PD_V_U64, 0).div_ceil(&pc(1, MAX_PD_V_U64, 0)), None);
+  }
+
   #[test]
   fn test_mul() {
     fn succeeds(
@@ -532,52 +2068,58 @@ mod test {
     succeeds(
       pc(3 * (PD_SCALE as i64), 3 * PD_SCALE, PD_EXPO),
       pc(2 * (PD_SCALE as i64), 4 * PD_SCALE, PD_EXPO),
-      pc(6 * ten_e7 * ten_e7, 18 * uten_e7 * uten_e7, -14)
+      pc(6 * ten_e7 * ten_e7 * 10_000, 18 * uten_e7 * uten_e7 * 10_000, -18)
     );
 
-    // Test with end range of possible inputs to identify overflow
-    // These inputs will lose precision due to the initial normalization.
-    // Get the rounded versions of these inputs in order to compute the expected results.
-    let normed = pc(i64::MAX, u64::MAX, 0).normalize().unwrap();
+    // The exact product is kept when it fits, even if the inputs are not normalized.
+    succeeds(
+      pc(123_456_789_012, 1000, -9),
+      pc(987_654_321_098, 2000, -9),
+      pc(1_219_326_311_365_858_862, 12_345_678_992, -13)
+    );
+    succeeds(
+      pc(-4_000_000_000, 1, -9),
+      pc(2_500_000_000, 1, -9),
+      pc(-1_000_000_000_000_000_000, 650_000_000, -17)
+    );
 
+    // Test with end range of possible inputs to identify overflow
+    // Only the result is rounded to fit into 64 bits.
     succeeds(
       pc(i64::MAX, u64::MAX, 0),
       pc(i64::MAX, u64::MAX, 0),
-      pc(normed.price * normed.price, 4 * ((normed.price * normed.price) as u64), normed.expo * 2)
+      pc(850_705_917_302_346_158, 3_402_823_669_209_384_635, 20)
     );
     succeeds(pc(i64::MAX, u64::MAX, 0),
              pc(1, 1, 0),
-             pc(normed.price, 3 * (normed.price as u64), normed.expo));
+             pc(922_337_203_685_477_581, 2_767_011_611_056_432_743, 1));
 
     succeeds(
       pc(i64::MAX, 1, 0),
       pc(i64::MAX, 1, 0),
-      pc(normed.price * normed.price, 0, normed.expo * 2)
+      pc(8_507_059_173_023_461_585, 2, 19)
     );
     succeeds(pc(i64::MAX, 1, 0),
              pc(1, 1, 0),
-             pc(normed.price, normed.price as u64, normed.expo));
-
-    let normed = pc(i64::MIN, u64::MAX, 0).normalize().unwrap();
-    let normed_c = (-normed.price) as u64;
+             pc(i64::MAX, i64::MAX as u64 + 1, 0));
 
     succeeds(
       pc(i64::MIN, u64::MAX, 0),
       pc(i64::MIN, u64::MAX, 0),
-      pc(normed.price * normed.price, 4 * (normed_c * normed_c), normed.expo * 2)
+      pc(850_705_917_302_346_159, 3_402_823_669_209_384_635, 20)
     );
     succeeds(pc(i64::MIN, u64::MAX, 0),
              pc(1, 1, 0),
-             pc(normed.price, 3 * normed_c, normed.expo));
+             pc(-922_337_203_685_477_581, 2_767_011_611_056_432_743, 1));
 
     succeeds(
       pc(i64::MIN, 1, 0),
       pc(i64::MIN, 1, 0),
-      pc(normed.price * normed.price, 0, normed.expo * 2)
+      pc(8_507_059_173_023_461_587, 2, 19)
     );
     succeeds(pc(i64::MIN, 1, 0),
              pc(1, 1, 0),
-             pc(normed.price, normed_c, normed.expo));
+             pc(i64::MIN, i64::MAX as u64 + 2, 0));
 
     // Exponent under/overflow.
     succeeds(pc(1, 1, i32::MAX), pc(1, 1, 0), pc(1, 2, i32::MAX));
@@ -588,4 +2130,705 @@ mod test {
     succeeds(pc(1, 1, i32::MIN), pc(1, 1, 1), pc(1, 2, i32::MIN + 1));
     fails(pc(1, 1, i32::MIN), pc(1, 1, -1));
   }
+
+  #[test]
+  fn test_reciprocal() {
+    fn div_approach(x: PriceConf, result_expo: i32) -> Option<PriceConf> {
+      pc(1, 0, 0).div(&x)?.scale_to_exponent(result_expo)
+    }
+
+    // Exact reciprocals agree with dividing one by the price.
+    for &(x, expo) in &[(pc(200, 2, -2), -4), (pc(4, 0, 0), -2), (pc(-8, 1, 3), -6), (pc(5, 0, -1), 0)] {
+      assert_eq!(x.reciprocal(expo), div_approach(x, expo));
+    }
+    assert_eq!(pc(200, 2, -2).reciprocal(-4), Some(pc(5000, 50, -4)));
+    assert_eq!(pc(-8, 1, 3).reciprocal(-6), Some(pc(-125, 16, -6)));
+
+    // Dividing one by the price keeps only a single digit here.
+    assert_eq!(pc(123_456_789, 0, -8).reciprocal(-9), Some(pc(810_000_007, 0, -9)));
+    assert_eq!(div_approach(pc(123_456_789, 0, -8), -9), Some(pc(800_000_000, 0, -9)));
+
+    // The relative uncertainty is preserved.
+    assert_eq!(pc(100, 1, 0).reciprocal(-6), Some(pc(10_000, 100, -6)));
+
+    assert_eq!(pc(0, 1, 0).reciprocal(0), None);
+    assert_eq!(pc(1, 0, -20).reciprocal(0), None);
+  }
+
+  #[test]
+  fn test_powi() {
+    // (1.50 +- 0.02)^2 = 2.25 +- 0.06
+    assert_eq!(pc(150, 2, -2).powi(2, -4), Some(pc(22500, 600, -4)));
+    let x = pc(150, 2, -2);
+    assert_eq!(x.powi(2, -4), x.mul(&x).unwrap().scale_to_exponent(-4));
+    assert_eq!(pc(-3, 0, 0).powi(3, 0), Some(pc(-27, 0, 0)));
+    assert_eq!(pc(2, 0, 0).powi(10, 0), Some(pc(1024, 0, 0)));
+    assert_eq!(pc(150, 2, -2).powi(1, -2), Some(pc(150, 2, -2)));
+    assert_eq!(pc(5, 1, 0).powi(0, -2), Some(pc(100, 0, -2)));
+
+    // 10^30 does not fit into an i64 with exponent 0, but does with a larger exponent.
+    assert_eq!(pc(10, 0, 0).powi(30, 0), None);
+    assert_eq!(pc(10, 0, 0).powi(30, 20), Some(pc(10_000_000_000, 0, 20)));
+
+    // The exponent of the intermediate products overflows for large n.
+    assert_eq!(pc(2, 0, 0).powi(u32::MAX, 0), None);
+    assert_eq!(pc(1, 0, 0).powi(u32::MAX, 0), Some(pc(1, 0, 0)));
+    assert_eq!(pc(-1, 0, 0).powi(u32::MAX, 0), Some(pc(-1, 0, 0)));
+  }
+
+  #[test]
+  fn test_mul_to_exponent() {
+    fn chained(x: PriceConf, y: PriceConf, expo: i32) -> Option<PriceConf> {
+      x.mul(&y)?.scale_to_exponent(expo)
+    }
+
+    for &(x, y, expo) in &[
+      (pc(15, 3, -1), pc(-15, 2, -1), 0),
+      (pc(123, 4, -2), pc(5, 1, 0), -3),
+      (pc(-7, 2, 3), pc(11, 0, -2), 1),
+    ] {
+      assert_eq!(x.mul_to_exponent(&y, expo), chained(x, y, expo));
+    }
+    assert_eq!(pc(15, 3, -1).mul_to_exponent(&pc(-15, 2, -1), 0), Some(pc(-2, 1, 0)));
+
+    // The product needs more than 64 bits, so `mul` rounds it before it is scaled again.
+    let x = pc(4_416_180_676_804_840_258, 0, -18);
+    let y = pc(1_495_904_808_088_160_768, 0, -18);
+    assert_eq!(x.mul_to_exponent(&y, -15), Some(pc(6_606_185_907_818_388, 0, -15)));
+    assert_eq!(chained(x, y, -15), Some(pc(6_606_185_907_818_389, 0, -15)));
+
+    assert_eq!(pc(i64::MAX, 0, 0).mul_to_exponent(&pc(2, 0, 0), 0), None);
+    assert_eq!(pc(1, 0, 0).mul_to_exponent(&pc(1, 0, 0), -19), None);
+    assert_eq!(pc(2, 0, 0).mul_to_exponent(&pc(2, 0, 0), 1), None);
+  }
+
+  #[test]
+  fn test_div_to_exponent() {
+    fn chained(x: PriceConf, y: PriceConf, expo: i32) -> Option<PriceConf> {
+      x.div(&y)?.scale_to_exponent(expo)
+    }
+
+    assert_eq!(pc(100, 10, 0).div_to_exponent(&pc(4, 1, 0), -2), Some(pc(2500, 875, -2)));
+    assert_eq!(pc(100, 10, 0).div_to_exponent(&pc(-4, 1, 0), -2), Some(pc(-2500, 875, -2)));
+    assert_eq!(pc(-2, 0, 0).div_to_exponent(&pc(3, 0, 0), -3), Some(pc(-667, 0, -3)));
+    assert_eq!(pc(5, 1, 3).div_to_exponent(&pc(2, 0, -3), 6), Some(pc(3, 1, 6)));
+
+    // `div` normalizes its inputs to 28 bits, which loses the trailing digits of the price.
+    let x = pc(1_234_567_890_123, 0, -9);
+    let y = pc(3, 0, 0);
+    assert_eq!(x.div_to_exponent(&y, -9), Some(pc(411_522_630_041, 0, -9)));
+    assert_eq!(chained(x, y, -9), Some(pc(411_522_630_000, 0, -9)));
+
+    assert_eq!(pc(1, 0, 0).div_to_exponent(&pc(0, 1, 0), 0), None);
+    assert_eq!(pc(1, 0, 0).div_to_exponent(&pc(3, 0, 0), 0), None);
+    assert_eq!(pc(0, 1, 0).div_to_exponent(&pc(3, 0, 0), 0), Some(pc(0, 1, 0)));
+    assert_eq!(pc(i64::MAX, 0, 0).div_to_exponent(&pc(1, 0, 0), -1), None);
+  }
+
+  #[test]
+  fn test_div_with_expo() {
+    fn chained(x: PriceConf, y: PriceConf, expo: i32) -> Option<PriceConf> {
+      x.div(&y)?.scale_to_exponent(expo)
+    }
+
+    assert_eq!(pc(100, 10, 0).div_with_expo(&pc(4, 1, 0), -2), Some(pc(2500, 875, -2)));
+    assert_eq!(pc(2, 0, 0).div_with_expo(&pc(3, 0, 0), -1), chained(pc(2, 0, 0), pc(3, 0, 0), -1));
+
+    // 123456789012345 / 7 = 17636684144620.71...: the two-step approach loses the digits that
+    // `div` drops when normalizing, and then rounds a second time.
+    let x = pc(123_456_789_012_345, 0, 0);
+    let y = pc(7, 0, 0);
+    assert_eq!(x.div_with_expo(&y, 0), Some(pc(17_636_684_144_621, 0, 0)));
+    assert_eq!(chained(x, y, 0), Some(pc(17_636_684_142_857, 0, 0)));
+
+    // 1 / 3 = 0.333...: a single rounding at the requested exponent
+    let x = pc(1, 0, 0);
+    let y = pc(3, 0, 0);
+    assert_eq!(x.div_with_expo(&y, -18), Some(pc(333_333_333_333_333_333, 0, -18)));
+    assert_eq!(chained(x, y, -18), Some(pc(333_333_333_000_000_000, 0, -18)));
+
+    assert_eq!(pc(1, 0, 0).div_with_expo(&pc(0, 0, 0), 0), None);
+  }
+
+  #[test]
+  fn test_add_to_exponent() {
+    fn chained(x: PriceConf, y: PriceConf, expo: i32) -> Option<PriceConf> {
+      x.scale_to_exponent(expo)?.add(&y.scale_to_exponent(expo)?)
+    }
+
+    assert_eq!(pc(123, 4, -2).add_to_exponent(&pc(5, 1, 0), -1), Some(pc(62, 11, -1)));
+    assert_eq!(pc(123, 4, -2).add_to_exponent(&pc(5, 1, 0), -3), Some(pc(6230, 1040, -3)));
+    assert_eq!(pc(-15, 0, -1).add_to_exponent(&pc(-10, 0, -1), 0), Some(pc(-3, 0, 0)));
+
+    // Rounding each argument before adding them rounds the same half twice.
+    let x = pc(15, 0, -1);
+    assert_eq!(x.add_to_exponent(&x, 0), Some(pc(3, 0, 0)));
+    assert_eq!(chained(x, x, 0), Some(pc(4, 0, 0)));
+
+    assert_eq!(pc(i64::MAX, 0, 0).add_to_exponent(&pc(1, 0, 0), 0), None);
+    assert_eq!(
+      pc(i64::MAX, 0, 0).add_to_exponent(&pc(1, 0, 0), 1),
+      Some(pc(922_337_203_685_477_581, 0, 1))
+    );
+    assert_eq!(pc(1, 0, 40).add_to_exponent(&pc(1, 0, 0), 40), None);
+  }
+
+  #[test]
+  fn test_add_same_expo() {
+    assert_eq!(pc(100, 10, -2).add_same_expo(&pc(30, 5, -2)), Some(pc(130, 15, -2)));
+    assert_eq!(pc(100, 10, -2).add_same_expo(&pc(-300, 5, -2)), Some(pc(-200, 15, -2)));
+    assert_eq!(pc(100, 10, -2).add_same_expo(&pc(30, 5, -2)), pc(100, 10, -2).add(&pc(30, 5, -2)));
+
+    // Mismatched exponents are not aligned.
+    assert_eq!(pc(100, 10, -2).add_same_expo(&pc(3, 1, -1)), None);
+    assert_eq!(pc(0, 0, 0).add_same_expo(&pc(0, 0, 1)), None);
+
+    assert_eq!(pc(i64::MAX, 0, 0).add_same_expo(&pc(1, 0, 0)), None);
+    assert_eq!(pc(i64::MIN, 0, 0).add_same_expo(&pc(-1, 0, 0)), None);
+    assert_eq!(pc(0, u64::MAX, 0).add_same_expo(&pc(0, 1, 0)), None);
+  }
+
+  #[test]
+  fn test_in_place_ops() {
+    let values = [pc(12345, 267, -2), pc(-678, 9, -2), pc(i64::MAX, 0, -2), pc(0, 5, -2), pc(3, 1, 4)];
+    for x in &values {
+      for y in &values {
+        let mut sum = *x;
+        assert_eq!(sum.add_in_place(y), x.add_same_expo(y).map(|_| ()));
+        assert_eq!(sum, x.add_same_expo(y).unwrap_or(*x));
+
+        let mut product = *x;
+        assert_eq!(product.mul_in_place(y), x.mul(y).map(|_| ()));
+        assert_eq!(product, x.mul(y).unwrap_or(*x));
+      }
+    }
+
+    // Accumulation
+    let mut acc = pc(0, 0, -2);
+    for x in &values[..2] {
+      acc.add_in_place(x).unwrap();
+    }
+    assert_eq!(acc, pc(11667, 276, -2));
+  }
+
+  #[test]
+  fn test_sub() {
+    assert_eq!(pc(100, 10, -2).sub(&pc(30, 5, -2)), Some(pc(70, 15, -2)));
+    assert_eq!(pc(30, 5, -2).sub(&pc(100, 10, -2)), Some(pc(-70, 15, -2)));
+    assert_eq!(pc(0, 0, 0).sub(&pc(i64::MAX, 0, 0)), Some(pc(-i64::MAX, 0, 0)));
+
+    assert_eq!(pc(i64::MIN, 0, 0).sub(&pc(1, 0, 0)), None);
+    assert_eq!(pc(0, u64::MAX, 0).sub(&pc(0, 1, 0)), None);
+  }
+
+  #[test]
+  fn test_clamp() {
+    let min = pc(90, 5, 0);
+    let max = pc(110, 5, 0);
+
+    // Within the band, including its bounds.
+    assert_eq!(pc(100, 3, 0).clamp(&min, &max), Some(pc(100, 3, 0)));
+    assert_eq!(pc(90, 3, 0).clamp(&min, &max), Some(pc(90, 3, 0)));
+    assert_eq!(pc(110, 3, 0).clamp(&min, &max), Some(pc(110, 3, 0)));
+    // Below and above the band. The confidence is widened by the distance moved.
+    assert_eq!(pc(80, 3, 0).clamp(&min, &max), Some(pc(90, 13, 0)));
+    assert_eq!(pc(115, 3, 0).clamp(&min, &max), Some(pc(110, 8, 0)));
+    assert_eq!(pc(-115, 0, 0).clamp(&pc(-100, 0, 0), &pc(100, 0, 0)), Some(pc(-100, 15, 0)));
+
+    // The exponents are aligned to the smallest one.
+    assert_eq!(pc(1234, 1, -2).clamp(&min, &max), Some(pc(9000, 7767, -2)));
+    assert_eq!(pc(10, 1, 1).clamp(&min, &max), Some(pc(100, 10, 0)));
+    assert_eq!(pc(12, 1, 1).clamp(&pc(9, 0, 1), &pc(1105, 0, -1)), Some(pc(1105, 195, -1)));
+
+    assert_eq!(pc(100, 3, 0).clamp(&max, &min), None);
+    assert_eq!(pc(i64::MAX, 3, 0).clamp(&min, &pc(1, 0, -20)), None);
+    assert_eq!(pc(i64::MIN, u64::MAX, 0).clamp(&pc(0, 0, 0), &max), None);
+    let bound = pc(i64::MAX, 0, 0);
+    assert_eq!(pc(i64::MIN, 0, 0).clamp(&bound, &bound), Some(pc(i64::MAX, u64::MAX, 0)));
+  }
+
+  #[test]
+  fn test_operators() {
+    let x = pc(12345, 267, -2);
+    let y = pc(-678, 9, -2);
+    assert_eq!(x + y, x.add(&y).unwrap());
+    assert_eq!(x + y, pc(11667, 276, -2));
+    assert_eq!(x - y, x.sub(&y).unwrap());
+    assert_eq!(x - y, pc(13023, 276, -2));
+    assert_eq!(x * y, x.mul(&y).unwrap());
+    assert_eq!(x / y, x.div(&y).unwrap());
+
+    // The operators are also implemented for references.
+    let (x_ref, y_ref) = (&x, &y);
+    assert_eq!(x_ref + y_ref, x + y);
+    assert_eq!(x_ref - y_ref, x - y);
+    assert_eq!(x_ref * y_ref, x * y);
+    assert_eq!(x_ref / y_ref, x / y);
+
+    // Operators compose.
+    let z = pc(2, 0, 0);
+    assert_eq!((x + y) * z, x.add(&y).unwrap().mul(&z).unwrap());
+  }
+
+  #[test]
+  #[should_panic(expected = "PriceConf addition overflowed")]
+  fn test_operator_overflow() {
+    let _ = pc(i64::MAX, 0, 0) + pc(1, 0, 0);
+  }
+
+  #[test]
+  #[should_panic(expected = "PriceConf division failed")]
+  fn test_operator_division_by_zero() {
+    let _ = pc(1, 0, 0) / pc(0, 0, 0);
+  }
+
+  #[test]
+  fn test_max_of_and_min_of() {
+    use core::cmp::Ordering;
+
+    let prices = [pc(12345, 267, -2), pc(12, 1, 1), pc(-5, 0, 3), pc(123_460, 9, -3)];
+    assert_eq!(PriceConf::max_of(&prices), Some(pc(123_460, 9, -3)));
+    assert_eq!(PriceConf::min_of(&prices), Some(pc(-5, 0, 3)));
+    for price in &prices {
+      assert_ne!(price.cmp_price(&PriceConf::max_of(&prices).unwrap()), Some(Ordering::Greater));
+      assert_ne!(price.cmp_price(&PriceConf::min_of(&prices).unwrap()), Some(Ordering::Less));
+    }
+
+    // Equal prices with different representations: the first one is returned.
+    let prices = [pc(100, 1, -2), pc(1, 2, 0), pc(1000, 3, -3)];
+    assert_eq!(PriceConf::max_of(&prices), Some(pc(100, 1, -2)));
+    assert_eq!(PriceConf::min_of(&prices), Some(pc(100, 1, -2)));
+
+    assert_eq!(PriceConf::max_of(&[pc(7, 0, 0)]), Some(pc(7, 0, 0)));
+    assert_eq!(PriceConf::max_of(&[pc(0, 0, 100), pc(0, 0, -100)]), Some(pc(0, 0, 100)));
+    assert_eq!(PriceConf::max_of(&[]), None);
+    assert_eq!(PriceConf::min_of(&[]), None);
+    // Aligning i64::MAX * 10^1 to exponent 0 overflows.
+    assert_eq!(PriceConf::max_of(&[pc(i64::MAX, 0, 1), pc(1, 0, 0)]), None);
+    assert_eq!(PriceConf::min_of(&[pc(1, 0, i32::MAX), pc(1, 0, i32::MIN)]), None);
+  }
+
+  #[test]
+  fn test_weighted_mean() {
+    fn succeeds(
+      prices: &[(PriceConf, u64)],
+      expected: PriceConf,
+    ) {
+      assert_eq!(PriceConf::weighted_mean(prices).unwrap(), expected);
+    }
+
+    fn fails(
+      prices: &[(PriceConf, u64)],
+    ) {
+      assert_eq!(PriceConf::weighted_mean(prices), None);
+    }
+
+    // Equal weights give the plain mean.
+    succeeds(&[(pc(100, 10, -2), 1)], pc(100, 10, -2));
+    succeeds(&[(pc(100, 10, -2), 1), (pc(200, 20, -2), 1)], pc(150, 15, -2));
+    succeeds(&[(pc(100, 10, -2), 7), (pc(200, 20, -2), 7)], pc(150, 15, -2));
+    succeeds(&[(pc(1, 1, 0), 1), (pc(2, 2, 0), 1), (pc(4, 4, 0), 1)], pc(2, 3, 0));
+    succeeds(&[(pc(-1, 0, 0), 1), (pc(-2, 0, 0), 1)], pc(-2, 0, 0));
+
+    // A dominant weight pulls the result toward its price.
+    succeeds(&[(pc(100, 10, -2), 99), (pc(200, 20, -2), 1)], pc(101, 11, -2));
+    succeeds(&[(pc(100, 10, -2), 1_000_000), (pc(200, 20, -2), 1)], pc(100, 11, -2));
+    succeeds(&[(pc(100, 10, -2), 0), (pc(200, 20, -2), 1)], pc(200, 20, -2));
+
+    // Inputs are aligned to the smallest exponent.
+    succeeds(&[(pc(1, 1, 0), 1), (pc(300, 10, -2), 1)], pc(200, 55, -2));
+
+    // Large values are accumulated without overflow.
+    succeeds(
+      &[(pc(i64::MAX, u64::MAX, 0), u64::MAX), (pc(i64::MAX, u64::MAX, 0), 1)],
+      pc(i64::MAX, u64::MAX, 0)
+    );
+    succeeds(&[(pc(i64::MIN, 0, 0), u64::MAX), (pc(i64::MIN, 0, 0), 1)], pc(i64::MIN, 0, 0));
+
+    fails(&[]);
+    fails(&[(pc(100, 10, -2), 0), (pc(200, 20, -2), 0)]);
+    // The weighted sum overflows.
+    fails(&[(pc(i64::MAX, 0, 0), u64::MAX), (pc(i64::MAX, 0, 0), u64::MAX)]);
+    // Aligning the exponents overflows.
+    fails(&[(pc(i64::MAX, 0, 0), 1), (pc(1, 0, -1), 1)]);
+  }
+
+  #[test]
+  fn test_percent_change_bps() {
+    // Gains
+    assert_eq!(pc(10150, 5, -2).percent_change_bps(&pc(10000, 1, -2)), Some(150));
+    assert_eq!(pc(200, 0, 0).percent_change_bps(&pc(100, 0, 0)), Some(10_000));
+    assert_eq!(pc(1, 0, 0).percent_change_bps(&pc(3, 0, 0)), Some(-6666));
+    // Losses, rounded toward zero
+    assert_eq!(pc(9850, 5, -2).percent_change_bps(&pc(10000, 1, -2)), Some(-150));
+    assert_eq!(pc(29990, 0, 0).percent_change_bps(&pc(30000, 0, 0)), Some(-3));
+    assert_eq!(pc(0, 0, 0).percent_change_bps(&pc(100, 0, 0)), Some(-10_000));
+    assert_eq!(pc(-100, 0, 0).percent_change_bps(&pc(100, 0, 0)), Some(-20_000));
+    // Zero change
+    assert_eq!(pc(10000, 5, -2).percent_change_bps(&pc(10000, 1, -2)), Some(0));
+    assert_eq!(pc(29999, 0, 0).percent_change_bps(&pc(30000, 0, 0)), Some(0));
+    // Negative reference prices: the sign follows the direction of the move.
+    assert_eq!(pc(-9850, 0, -2).percent_change_bps(&pc(-10000, 0, -2)), Some(150));
+
+    // Mixed exponents: 101.5 vs 100
+    assert_eq!(pc(1015, 0, -1).percent_change_bps(&pc(100, 0, 0)), Some(150));
+    assert_eq!(pc(10, 0, 1).percent_change_bps(&pc(10000, 0, -2)), Some(0));
+    assert_eq!(pc(0, 0, i32::MAX).percent_change_bps(&pc(1, 0, 0)), Some(-10_000));
+
+    assert_eq!(pc(100, 0, 0).percent_change_bps(&pc(0, 0, 0)), None);
+    assert_eq!(pc(i64::MAX, 0, 0).percent_change_bps(&pc(1, 0, 0)), None);
+    assert_eq!(pc(1, 0, 40).percent_change_bps(&pc(1, 0, 0)), None);
+    assert_eq!(pc(i64::MIN, 0, 0).percent_change_bps(&pc(i64::MAX, 0, 0)), Some(-20_000));
+  }
+
+  #[test]
+  fn test_get_confidence_ratio_bps() {
+    assert_eq!(pc(10_000, 200, -2).get_confidence_ratio_bps(), Some(200));
+    assert_eq!(pc(-10_000, 200, -2).get_confidence_ratio_bps(), Some(200));
+    assert_eq!(pc(3, 1, 5).get_confidence_ratio_bps(), Some(3333));
+    assert_eq!(pc(1, 0, 0).get_confidence_ratio_bps(), Some(0));
+    assert_eq!(pc(i64::MIN, u64::MAX, 0).get_confidence_ratio_bps(), Some(19_999));
+
+    assert_eq!(pc(0, 1, 0).get_confidence_ratio_bps(), None);
+    assert_eq!(pc(1, u64::MAX, 0).get_confidence_ratio_bps(), None);
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn test_to_f64() {
+    assert_eq!(pc(12345, 267, -2).to_f64(), 123.45);
+    assert_eq!(pc(12345, 267, -2).conf_to_f64(), 2.67);
+    assert_eq!(pc(-5, 1, 3).to_f64(), -5000.0);
+    assert_eq!(pc(0, 0, -8).to_f64(), 0.0);
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn test_from_f64() {
+    assert_eq!(PriceConf::from_f64(123.45, 2.67, -2), Some(pc(12345, 267, -2)));
+    assert_eq!(PriceConf::from_f64(-123.456, 0.004, -2), Some(pc(-12346, 0, -2)));
+    assert_eq!(PriceConf::from_f64(12345.0, 100.0, 2), Some(pc(123, 1, 2)));
+
+    assert_eq!(PriceConf::from_f64(f64::NAN, 1.0, 0), None);
+    assert_eq!(PriceConf::from_f64(1.0, f64::INFINITY, 0), None);
+    assert_eq!(PriceConf::from_f64(1.0, -1.0, 0), None);
+    assert_eq!(PriceConf::from_f64(1e19, 0.0, 0), None);
+    assert_eq!(PriceConf::from_f64(-1e19, 0.0, 0), None);
+    assert_eq!(PriceConf::from_f64(1.0, 1e20, 0), None);
+    assert_eq!(PriceConf::from_f64(1.0, 0.0, i32::MIN), None);
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn test_f64_round_trip() {
+    for (value, conf, expo) in [(57000.12, 3.4, -8), (-0.0042, 0.0001, -9), (1.5e12, 2.5e9, 3), (0.9876, 0.0, -4)] {
+      let price = PriceConf::from_f64(value, conf, expo).unwrap();
+      let tolerance = 10f64.powi(expo);
+      assert!((price.to_f64() - value).abs() <= tolerance);
+      assert!((price.conf_to_f64() - conf).abs() <= tolerance);
+    }
+  }
+
+  #[test]
+  fn test_display() {
+    assert_eq!(pc(12345, 267, -2).to_string(), "123.45 ± 2.67");
+    assert_eq!(pc(123, 1, 2).to_string(), "12300 ± 100");
+    assert_eq!(pc(123, 1, 0).to_string(), "123 ± 1");
+    assert_eq!(pc(5, 1, -3).to_string(), "0.005 ± 0.001");
+    assert_eq!(pc(-5, 10, -2).to_string(), "-0.05 ± 0.10");
+    assert_eq!(pc(-12345, 0, -2).to_string(), "-123.45 ± 0.00");
+    assert_eq!(pc(0, 0, -2).to_string(), "0.00 ± 0.00");
+    assert_eq!(pc(0, 0, 3).to_string(), "0 ± 0");
+    assert_eq!(pc(i64::MIN, u64::MAX, -10).to_string(), "-922337203.6854775808 ± 1844674407.3709551615");
+  }
+
+  #[test]
+  fn test_geometric_mean() {
+    fn succeeds(prices: &[PriceConf], result_expo: i32, expected: PriceConf) {
+      assert_eq!(PriceConf::geometric_mean(prices, result_expo), Some(expected));
+    }
+
+    succeeds(&[pc(4, 0, 0), pc(9, 0, 0)], 0, pc(6, 0, 0));
+    succeeds(&[pc(4, 0, 0), pc(9, 0, 0)], -9, pc(6_000_000_000, 0, -9));
+    succeeds(&[pc(2, 0, 0), pc(8, 0, 0)], -2, pc(400, 0, -2));
+    succeeds(&[pc(1, 0, 0), pc(1, 0, 0), pc(1, 0, 0)], -6, pc(1_000_000, 0, -6));
+    succeeds(&[pc(12345, 0, -2)], -2, pc(12345, 0, -2));
+    // The inputs may have different exponents: sqrt(1.00 * 400) = 20
+    succeeds(&[pc(100, 0, -2), pc(4, 0, 2)], -3, pc(20_000, 0, -3));
+    // cbrt(2 * 3 * 4) = cbrt(24) = 2.884499140614816...
+    succeeds(&[pc(2, 0, 0), pc(3, 0, 0), pc(4, 0, 0)], -12, pc(2_884_499_140_615, 0, -12));
+    // sqrt(2 * 3) = 2.449489742783178...
+    succeeds(&[pc(2, 0, 0), pc(3, 0, 0)], -15, pc(2_449_489_742_783_178, 0, -15));
+    succeeds(&[pc(2, 0, 0), pc(3, 0, 0)], 0, pc(2, 0, 0));
+    // Extreme exponents
+    succeeds(&[pc(1, 0, 1000), pc(1, 0, -1000)], -2, pc(100, 0, -2));
+    succeeds(&[pc(i64::MAX, 0, 0), pc(i64::MAX, 0, 0)], 3, pc(9_223_372_036_854_776, 0, 3));
+    succeeds(&[pc(25, 0, i32::MIN), pc(4, 0, i32::MIN)], i32::MIN, pc(10, 0, i32::MIN));
+
+    // The relative confidence is the mean of 10% and 5%: 6 +- 7.5%
+    succeeds(&[pc(400, 40, -2), pc(900, 45, -2)], -2, pc(600, 45, -2));
+    succeeds(&[pc(400, 40, -2), pc(900, 45, -2)], 0, pc(6, 1, 0));
+    succeeds(&[pc(1, u64::MAX, 0)], 0, pc(1, u64::MAX, 0));
+    assert_eq!(PriceConf::geometric_mean(&[pc(2, u64::MAX, 0)], -1), None);
+
+    // Invalid inputs and results out of range
+    assert_eq!(PriceConf::geometric_mean(&[], 0), None);
+    assert_eq!(PriceConf::geometric_mean(&[pc(4, 0, 0), pc(0, 0, 0)], 0), None);
+    assert_eq!(PriceConf::geometric_mean(&[pc(4, 0, 0), pc(-9, 0, 0)], 0), None);
+    assert_eq!(PriceConf::geometric_mean(&[pc(4, 0, 0), pc(9, 0, 0)], -19), None);
+    assert_eq!(PriceConf::geometric_mean(&[pc(4, 0, 0), pc(9, 0, 0)], 1), Some(pc(1, 0, 1)));
+    assert_eq!(PriceConf::geometric_mean(&[pc(4, 0, 0), pc(9, 0, 0)], 2), None);
+  }
+
+  #[test]
+  fn test_combine_inverse_variance() {
+    fn succeeds(prices: &[PriceConf], result_expo: i32, expected: PriceConf) {
+      let combined = PriceConf::combine_inverse_variance(prices, result_expo).unwrap();
+      assert_eq!(combined, expected);
+    }
+
+    fn fails(prices: &[PriceConf], result_expo: i32) {
+      assert_eq!(PriceConf::combine_inverse_variance(prices, result_expo), None);
+    }
+
+    // Equal confidences give the plain mean, with the confidence divided by sqrt(2).
+    succeeds(&[pc(100, 10, -2), pc(110, 10, -2)], -2, pc(105, 8, -2));
+    succeeds(&[pc(100, 10, -2), pc(110, 10, -2)], -4, pc(10500, 708, -4));
+    succeeds(&[pc(12345, 267, -2)], -2, pc(12345, 267, -2));
+    // Weights 1/9 and 1/16: (100 * 16 + 200 * 9) / 25 = 136, conf = 3 * 4 / 5 = 2.4
+    succeeds(&[pc(100, 3, 0), pc(200, 4, 0)], 0, pc(136, 3, 0));
+    succeeds(&[pc(100, 3, 0), pc(200, 4, 0)], -1, pc(1360, 24, -1));
+    succeeds(&[pc(-100, 3, 0), pc(-200, 4, 0)], -1, pc(-1360, 24, -1));
+    // The inputs may have different exponents.
+    succeeds(&[pc(100, 3, 0), pc(2000, 40, -1)], -1, pc(1360, 24, -1));
+    succeeds(&[pc(100, 3, 0), pc(200, 4, 0)], 1, pc(14, 1, 1));
+    // A much wider source barely moves the result.
+    succeeds(&[pc(100_000, 10, -3), pc(200_000, 100_000, -3)], -3, pc(100_000, 10, -3));
+    // Inputs with a zero confidence are ignored.
+    succeeds(&[pc(100, 0, 0), pc(200, 10, 0)], 0, pc(200, 10, 0));
+    // Their exponents are ignored too, so they cannot push the others out of range.
+    succeeds(&[pc(100, 3, 0), pc(1, 0, -30)], 0, pc(100, 3, 0));
+    succeeds(&[pc(i64::MAX, 1, 0), pc(i64::MAX, 1, 0)], 0, pc(i64::MAX, 1, 0));
+    succeeds(&[pc(i64::MIN, 1, 0)], 0, pc(i64::MIN, 1, 0));
+
+    // The combined confidence is tighter than the tightest input.
+    let prices = [pc(1_000_000, 1_000, -4), pc(1_002_000, 1_500, -4), pc(998_000, 3_000, -4)];
+    let combined = PriceConf::combine_inverse_variance(&prices, -4).unwrap();
+    assert!(combined.conf < 1_000);
+    assert_eq!(combined, pc(1_000_429, 802, -4));
+
+    fails(&[], 0);
+    fails(&[pc(100, 0, 0), pc(200, 0, 0)], 0);
+    fails(&[pc(i64::MAX, 1, 0), pc(1, 1, -1)], 0);
+    fails(&[pc(100, 3, 0), pc(200, 4, 0)], 3);
+    fails(&[pc(i64::MAX, 1, 0)], -1);
+  }
+
+  #[test]
+  fn test_to_i128_scaled() {
+    // Scaling up is exact.
+    assert_eq!(pc(12345, 267, -2).to_i128_scaled(-2), Some(12345));
+    assert_eq!(pc(12345, 267, -2).to_i128_scaled(-18), Some(123_450_000_000_000_000_000));
+    assert_eq!(pc(-12345, 267, -2).to_i128_scaled(-18), Some(-123_450_000_000_000_000_000));
+    assert_eq!(pc(i64::MAX, 0, 0).to_i128_scaled(-19), Some(i64::MAX as i128 * 10i128.pow(19)));
+    assert_eq!(pc(i64::MIN, 0, 0).to_i128_scaled(-19), Some(i64::MIN as i128 * 10i128.pow(19)));
+    assert_eq!(pc(1, 0, 0).to_i128_scaled(-38), Some(10i128.pow(38)));
+
+    // Scaling down rounds to nearest, with ties away from zero.
+    assert_eq!(pc(12345, 267, -2).to_i128_scaled(0), Some(123));
+    assert_eq!(pc(12350, 267, -2).to_i128_scaled(0), Some(124));
+    assert_eq!(pc(-12350, 267, -2).to_i128_scaled(0), Some(-124));
+    assert_eq!(pc(50, 0, -2).to_i128_scaled(0), Some(1));
+    assert_eq!(pc(i64::MIN, 0, 0).to_i128_scaled(18), Some(-9));
+    assert_eq!(pc(0, 5, 0).to_i128_scaled(i32::MAX), Some(0));
+    assert_eq!(pc(0, 5, 0).to_i128_scaled(i32::MIN), Some(0));
+
+    // Overflow, and nonzero prices that round to 0
+    assert_eq!(pc(i64::MAX, 0, 0).to_i128_scaled(-20), None);
+    assert_eq!(pc(1, 0, 0).to_i128_scaled(-39), None);
+    assert_eq!(pc(1, 0, i32::MAX).to_i128_scaled(i32::MIN), None);
+    assert_eq!(pc(49, 0, -2).to_i128_scaled(0), None);
+    assert_eq!(pc(i64::MAX, 0, 0).to_i128_scaled(40), None);
+  }
+
+  #[test]
+  fn test_to_scaled_integer() {
+    // 123.456789 in basis points (10^-4) and at 10^-8
+    let x = pc(123_456_789, 1_000, -6);
+    assert_eq!(x.to_scaled_integer(-4), Some(1_234_568));
+    assert_eq!(x.to_scaled_integer(-8), Some(12_345_678_900));
+    assert_eq!(x.to_scaled_integer(-6), Some(123_456_789));
+
+    // Rounding down and up, with ties away from zero
+    assert_eq!(pc(123_449, 0, -3).to_scaled_integer(-1), Some(1_234));
+    assert_eq!(pc(123_450, 0, -3).to_scaled_integer(-1), Some(1_235));
+    assert_eq!(pc(123_451, 0, -3).to_scaled_integer(-1), Some(1_235));
+    assert_eq!(pc(-123_449, 0, -3).to_scaled_integer(-1), Some(-1_234));
+    assert_eq!(pc(-123_450, 0, -3).to_scaled_integer(-1), Some(-1_235));
+
+    // The confidence does not need to fit at the new exponent.
+    assert_eq!(pc(1, u64::MAX, 0).to_scaled_integer(-8), Some(100_000_000));
+    assert_eq!(pc(1, u64::MAX, 0).scale_to_exponent(-8), None);
+
+    assert_eq!(pc(i64::MAX, 0, 0).to_scaled_integer(0), Some(i64::MAX));
+    assert_eq!(pc(i64::MAX, 0, 0).to_scaled_integer(-1), None);
+    assert_eq!(pc(i64::MIN, 0, 0).to_scaled_integer(-1), None);
+    assert_eq!(pc(4, 0, -2).to_scaled_integer(0), None);
+    assert_eq!(pc(0, 0, -2).to_scaled_integer(0), Some(0));
+  }
+
+  #[test]
+  fn test_bounds() {
+    assert_eq!(pc(12345, 267, -2).lower_bound(), Some(pc(12078, 0, -2)));
+    assert_eq!(pc(12345, 267, -2).upper_bound(), Some(pc(12612, 0, -2)));
+    assert_eq!(pc(-5, 10, 3).lower_bound(), Some(pc(-15, 0, 3)));
+    assert_eq!(pc(-5, 10, 3).upper_bound(), Some(pc(5, 0, 3)));
+    assert_eq!(pc(7, 0, 0).lower_bound(), Some(pc(7, 0, 0)));
+
+    // Near the extremes of i64
+    assert_eq!(pc(i64::MAX - 1, 1, 0).upper_bound(), Some(pc(i64::MAX, 0, 0)));
+    assert_eq!(pc(i64::MAX, 1, 0).upper_bound(), None);
+    assert_eq!(pc(i64::MAX, 1, 0).lower_bound(), Some(pc(i64::MAX - 1, 0, 0)));
+    assert_eq!(pc(i64::MIN + 1, 1, 0).lower_bound(), Some(pc(i64::MIN, 0, 0)));
+    assert_eq!(pc(i64::MIN, 1, 0).lower_bound(), None);
+    assert_eq!(pc(i64::MIN, u64::MAX, 0).upper_bound(), Some(pc(i64::MAX, 0, 0)));
+    assert_eq!(pc(0, u64::MAX, 0).upper_bound(), None);
+    assert_eq!(pc(0, u64::MAX, 0).lower_bound(), None);
+  }
+
+  #[test]
+  fn test_to_ticks() {
+    let tick = pc(5, 0, -2);
+
+    // Exact multiples of 0.05
+    assert_eq!(pc(12345, 10, -2).to_ticks(&tick), Some(2469));
+    assert_eq!(pc(-12345, 0, -2).to_ticks(&tick), Some(-2469));
+    assert_eq!(pc(0, 7, -2).to_ticks(&tick), Some(0));
+    assert_eq!(pc(2, 0, 3).to_ticks(&tick), Some(40_000));
+    assert_eq!(pc(10_000, 0, -5).to_ticks(&tick), Some(2));
+
+    // Rounding to the nearest tick, with ties away from zero
+    assert_eq!(pc(12346, 0, -2).to_ticks(&tick), Some(2469));
+    assert_eq!(pc(12347, 0, -2).to_ticks(&tick), Some(2469));
+    assert_eq!(pc(123475, 0, -3).to_ticks(&tick), Some(2470));
+    assert_eq!(pc(-123475, 0, -3).to_ticks(&tick), Some(-2470));
+    assert_eq!(pc(-123474, 0, -3).to_ticks(&tick), Some(-2469));
+    assert_eq!(pc(2, 0, -2).to_ticks(&tick), Some(0));
+    assert_eq!(pc(i64::MAX, 0, -22).to_ticks(&tick), Some(0));
+    assert_eq!(pc(i64::MIN, 0, -60).to_ticks(&pc(1, 0, 0)), Some(0));
+
+    // Extremes of the result
+    assert_eq!(pc(i64::MAX, 0, -2).to_ticks(&pc(1, 0, -2)), Some(i64::MAX));
+    assert_eq!(pc(i64::MIN, 0, -2).to_ticks(&pc(1, 0, -2)), Some(i64::MIN));
+    assert_eq!(pc(i64::MIN, 0, 0).to_ticks(&pc(1, 0, -1)), None);
+    assert_eq!(pc(i64::MAX, 0, 0).to_ticks(&pc(1, 0, -1)), None);
+    assert_eq!(pc(1, 0, 100).to_ticks(&pc(1, 0, 0)), None);
+    assert_eq!(pc(0, 0, 100).to_ticks(&pc(1, 0, 0)), Some(0));
+
+    // Invalid tick sizes
+    assert_eq!(pc(12345, 0, -2).to_ticks(&pc(0, 0, -2)), None);
+    assert_eq!(pc(12345, 0, -2).to_ticks(&pc(-5, 0, -2)), None);
+  }
+
+  #[test]
+  fn test_decimal_string() {
+    assert_eq!(pc(12345, 267, -2).to_decimal_string(), "123.45");
+    assert_eq!(pc(12345, 267, -2).conf_to_decimal_string(), "2.67");
+    assert_eq!(pc(-12345, 0, -2).to_decimal_string(), "-123.45");
+    assert_eq!(pc(-5, 10, -4).to_decimal_string(), "-0.0005");
+    assert_eq!(pc(-5, 10, -4).conf_to_decimal_string(), "0.0010");
+    assert_eq!(pc(123, 1, 0).to_decimal_string(), "123");
+    assert_eq!(pc(123, 1, 2).to_decimal_string(), "12300");
+    assert_eq!(pc(-7, 1, 20).to_decimal_string(), "-700000000000000000000");
+    assert_eq!(pc(-7, 1, 20).conf_to_decimal_string(), "100000000000000000000");
+    assert_eq!(pc(0, 0, 5).to_decimal_string(), "0");
+    assert_eq!(pc(0, 0, -3).to_decimal_string(), "0.000");
+    assert_eq!(pc(i64::MIN, 0, -19).to_decimal_string(), "-0.9223372036854775808");
+    assert_eq!(pc(0, u64::MAX, -3).conf_to_decimal_string(), "18446744073709551.615");
+  }
+
+  #[test]
+  fn test_cmp_price() {
+    use std::cmp::Ordering;
+
+    assert_eq!(pc(100, 0, -2).cmp_price(&pc(1, 0, 0)), Some(Ordering::Equal));
+    assert_ne!(pc(100, 0, -2), pc(1, 0, 0));
+    assert_eq!(pc(101, 0, -2).cmp_price(&pc(1, 0, 0)), Some(Ordering::Greater));
+    assert_eq!(pc(99, 0, -2).cmp_price(&pc(1, 0, 0)), Some(Ordering::Less));
+    assert_eq!(pc(-1, 0, 5).cmp_price(&pc(1, 0, -5)), Some(Ordering::Less));
+    assert_eq!(pc(0, 0, 100).cmp_price(&pc(0, 0, -100)), Some(Ordering::Equal));
+
+    // The confidence interval is ignored.
+    assert_eq!(pc(5, 1, 0).cmp_price(&pc(5, 1000, 0)), Some(Ordering::Equal));
+
+    // Alignment overflows
+    assert_eq!(pc(i64::MAX, 0, 1).cmp_price(&pc(1, 0, 0)), None);
+    assert_eq!(pc(1, 0, i32::MAX).cmp_price(&pc(1, 0, i32::MIN)), None);
+    assert_eq!(pc(1, 0, 18).cmp_price(&pc(i64::MAX, 0, 0)), Some(Ordering::Less));
+  }
+
+  #[test]
+  fn test_cmul() {
+    fn succeeds(
+      price1: PriceConf,
+      c: i64,
+      e: i32,
+      expected: PriceConf,
+    ) {
+      assert_eq!(price1.cmul(c, e).unwrap(), expected);
+    }
+
+    fn fails(
+      price1: PriceConf,
+      c: i64,
+      e: i32,
+    ) {
+      assert_eq!(price1.cmul(c, e), None);
+    }
+
+    succeeds(pc(1, 1, 0), 1, 0, pc(1, 1, 0));
+    succeeds(pc(10, 1, -2), 3, -1, pc(30, 3, -3));
+    succeeds(pc(10, 1, -2), -3, 1, pc(-30, 3, -1));
+    succeeds(pc(-10, 1, -2), -3, 0, pc(30, 3, -2));
+    succeeds(pc(10, 1, -2), 0, 0, pc(0, 0, -2));
+
+    // Large quantities are not truncated to 27 bits.
+    succeeds(pc(i64::MAX, 1, 0), 1, 5, pc(i64::MAX, 1, 5));
+    succeeds(pc(123_456_789, 1, -8), 987_654_321, -9, pc(121_932_631_112_635_269, 987_654_321, -17));
+
+    // 1.5 +- 0.001 USD times a supply of 5e8 tokens with 9 decimals: 7.5e8 +- 5e5 USD
+    succeeds(
+      pc(150_000_000, 100_000, -8),
+      500_000_000_000_000_000,
+      -9,
+      pc(7_500_000_000_000_000_000, 5_000_000_000_000_000, -10)
+    );
+
+    // The product needs more than 64 bits, so digits are dropped, rounding the price to nearest
+    // and the confidence up.
+    succeeds(pc(i64::MAX, u64::MAX, 0), 10, 0, pc(i64::MAX, u64::MAX, 1));
+    succeeds(pc(i64::MAX, 1, 0), 11, 0, pc(1_014_570_924_054_025_339, 1, 2));
+    succeeds(pc(i64::MIN, 0, 0), i64::MIN, 0, pc(8_507_059_173_023_461_587, 0, 19));
+    succeeds(pc(-i64::MAX, 0, 0), 15, 0, pc(-1_383_505_805_528_216_371, 0, 2));
+    succeeds(pc(123_456_789_012, 1, -8), 987_654_321, -9, pc(1_219_326_311_244_871_209, 9_876_544, -15));
+
+    // Rounding up carries the result out of range, so a further digit is dropped.
+    succeeds(pc(i64::MAX, 0, 0), 10, 0, pc(i64::MAX, 0, 1));
+    succeeds(pc(3_689_348_814_741_910_323, 0, 0), 25, 0, pc(922_337_203_685_477_581, 0, 2));
+    succeeds(pc(0, u64::MAX, 0), 11, 0, pc(0, 2_029_141_848_108_050_678, 2));
+    succeeds(pc(0, 4_985_606_506_407_986_923, 0), 37, 0, pc(0, 1_844_674_407_370_955_162, 2));
+
+    // Exponent overflow
+    fails(pc(1, 1, i32::MAX), 1, 1);
+    fails(pc(i64::MAX, 1, i32::MAX), 10, 0);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde_round_trip() {
+    let price = pc(-12345, 267, -2);
+    let json = serde_json::to_string(&price).unwrap();
+    assert_eq!(json, r#"{"price":-12345,"conf":267,"expo":-2}"#);
+    assert_eq!(serde_json::from_str::<PriceConf>(&json).unwrap(), price);
+  }
 }
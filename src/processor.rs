@@ -9,7 +9,7 @@ use solana_program::{
 };
 
 use crate::{
-  instruction::PythClientInstruction, load_price,
+  instruction::PythClientInstruction, load_price, PriceConf,
 };
 
 pub fn process_instruction(
@@ -31,25 +31,57 @@ pub fn process_instruction(
       x.add(&y);
       Ok(())
     }
+    PythClientInstruction::Subtract { x, y } => {
+      x.sub(&y);
+      Ok(())
+    }
     PythClientInstruction::Normalize { x } => {
       x.normalize();
       Ok(())
     }
+    PythClientInstruction::Cmul { x, c, e } => {
+      x.cmul(c, e);
+      Ok(())
+    }
+    PythClientInstruction::WeightedMean { prices } => {
+      PriceConf::weighted_mean(&prices);
+      Ok(())
+    }
     PythClientInstruction::ScaleToExponent { x, expo } => {
       x.scale_to_exponent(expo);
       Ok(())
     }
+    PythClientInstruction::ToExponentNormalized { x, expo } => {
+      x.to_exponent_normalized(expo);
+      Ok(())
+    }
     PythClientInstruction::Noop => {
       Ok(())
     }
-    PythClientInstruction::PriceStatusCheck { price_account_data, expected_price_status } => {
+    PythClientInstruction::PriceStatusCheck { price_account_data, current_slot, expected_price_status } => {
       let price = load_price(&price_account_data[..])?;
       
-      if price.get_current_price_status() == expected_price_status {
+      if price.get_price_status_at(current_slot) == expected_price_status {
         Ok(())
       } else {
         Err(ProgramError::Custom(0))
       }
     }
+    PythClientInstruction::PriceNoOlderThan { price_account_data, current_slot, max_slot_age } => {
+      let price = load_price(&price_account_data[..])?;
+      price.get_price_no_older_than(current_slot, max_slot_age);
+      Ok(())
+    }
+    PythClientInstruction::BatchStatusCheck { accounts, current_slot, expected } => {
+      for (i, price_account_data) in accounts.iter().enumerate() {
+        // A load failure is reported with the same index encoding as a status mismatch, so that
+        // `Custom(i)` always refers to account `i`.
+        let price = load_price(&price_account_data[..]).map_err(|_| ProgramError::Custom(i as u32))?;
+        if price.get_price_status_at(current_slot) != expected {
+          return Err(ProgramError::Custom(i as u32));
+        }
+      }
+      Ok(())
+    }
   }
 }
@@ -0,0 +1,246 @@
+//! Helpers for writing tests against Pyth accounts and prices, enabled by the `test-utils` feature.
+
+use core::mem::size_of;
+
+use alloc::vec::Vec;
+
+use crate::{
+  AccountType, Price, PriceComp, PriceConf, PriceStatus, PriceType, MAGIC, MAX_PD_V_I64, PD_EXPO,
+  VERSION_2,
+};
+
+/**
+ * A builder for well-formed `Price` accounts. The account starts out with a valid header, a
+ * trading aggregate price of 0 with exponent 0 published at slot 1, and no components:
+ *
+ * ```
+ * use pyth_client::{load_price, test_utils::PriceBuilder, PriceConf};
+ *
+ * let data = PriceBuilder::new().with_price(12345, 267).with_expo(-2).to_bytes();
+ * let price = load_price(&data).unwrap();
+ * assert_eq!(price.get_current_price(), Some(PriceConf { price: 12345, conf: 267, expo: -2 }));
+ * ```
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct PriceBuilder {
+  price: Price,
+}
+
+impl Default for PriceBuilder {
+  fn default() -> Self {
+    PriceBuilder::new()
+  }
+}
+
+impl PriceBuilder {
+  /** Start building a price account with the defaults described above. */
+  pub fn new() -> PriceBuilder {
+    let mut price = Price {
+      magic: MAGIC,
+      ver: VERSION_2,
+      atype: AccountType::Price as u32,
+      size: size_of::<Price>() as u32,
+      ptype: PriceType::Price,
+      ..Default::default()
+    };
+    price.agg.status = PriceStatus::Trading;
+    PriceBuilder { price }.with_publish_slot(1)
+  }
+
+  /** Set the status of the aggregate price. */
+  pub fn with_status(mut self, status: PriceStatus) -> PriceBuilder {
+    self.price.agg.status = status;
+    self
+  }
+
+  /** Set the aggregate price and confidence interval. */
+  pub fn with_price(mut self, price: i64, conf: u64) -> PriceBuilder {
+    self.price.agg.price = price;
+    self.price.agg.conf = conf;
+    self
+  }
+
+  /** Set the exponent of all prices in the account. */
+  pub fn with_expo(mut self, expo: i32) -> PriceBuilder {
+    self.price.expo = expo;
+    self
+  }
+
+  /**
+   * Set the slot in which the aggregate price was published. This is also used as the valid slot,
+   * as a price account with a zero valid slot has never had a valid aggregate price.
+   */
+  pub fn with_publish_slot(mut self, slot: u64) -> PriceBuilder {
+    self.price.agg.pub_slot = slot;
+    self.price.valid_slot = slot;
+    self
+  }
+
+  /**
+   * Set the publisher components. `num` is set to the number of components, and `num_qt` to the
+   * number of components whose aggregate price is trading.
+   *
+   * Panics if there are more than 32 components, the capacity of a price account.
+   */
+  pub fn with_components(mut self, components: &[PriceComp]) -> PriceBuilder {
+    assert!(components.len() <= self.price.comp.len(), "too many components");
+    self.price.comp = Default::default();
+    self.price.comp[..components.len()].copy_from_slice(components);
+    self.price.num = components.len() as u32;
+    self.price.num_qt =
+      components.iter().filter(|comp| comp.agg.status == PriceStatus::Trading).count() as u32;
+    self
+  }
+
+  /** Get the `Price` built so far. */
+  pub fn build(&self) -> Price {
+    self.price
+  }
+
+  /**
+   * Get the account data of the `Price` built so far, which can be passed to `load_price`.
+   * Only available on little-endian targets, where the in-memory layout of `Price` matches the
+   * account data.
+   */
+  #[cfg(target_endian = "little")]
+  pub fn to_bytes(&self) -> Vec<u8> {
+    bytemuck::bytes_of(&self.price).to_vec()
+  }
+}
+
+/**
+ * Generate a `PriceConf` from `seed`, for fuzz targets and property tests of the arithmetic
+ * methods. The same seed always gives the same value. Rather than being uniformly distributed, the
+ * values favor edge cases: zero prices and confidences, prices near `i64::MIN` and `i64::MAX`,
+ * confidences near `u64::MAX`, and exponents near `i32::MIN` and `i32::MAX`, mixed with ordinary
+ * values.
+ */
+pub fn arbitrary_price_conf(seed: u64) -> PriceConf {
+  let mut state = seed;
+  let mut next = || {
+    // splitmix64, which gives well-mixed outputs even for consecutive seeds
+    state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  };
+
+  let (selector, small, random) = (next(), next() % 1000, next());
+  let sign = if random & 1 == 0 { 1 } else { -1 };
+  let price = match selector % 8 {
+    0 => 0,
+    1 => i64::MAX - small as i64,
+    2 => i64::MIN + small as i64,
+    3 => sign,
+    4 => sign * small as i64,
+    5 => sign * 10i64.pow((small % 19) as u32),
+    6 => (random % (2 * MAX_PD_V_I64 as u64 + 1)) as i64 - MAX_PD_V_I64,
+    _ => random as i64,
+  };
+
+  let (selector, small, random) = (next(), next() % 1000, next());
+  let conf = match selector % 6 {
+    0 => 0,
+    1 => u64::MAX - small,
+    2 => small,
+    3 => price.unsigned_abs() / (small + 1),
+    4 => random % (MAX_PD_V_I64 as u64 + 1),
+    _ => random,
+  };
+
+  let (selector, small, random) = (next(), next() % 1000, next());
+  let expo = match selector % 6 {
+    0 => 0,
+    1 => i32::MIN + small as i32,
+    2 => i32::MAX - small as i32,
+    3 => PD_EXPO,
+    4 => (small % 41) as i32 - 20,
+    _ => random as i32,
+  };
+
+  PriceConf { price, conf, expo }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::test_utils::{arbitrary_price_conf, PriceBuilder};
+  use crate::{PriceComp, PriceConf, PriceInfo, PriceStatus, MAX_PD_V_U64, MIN_PD_V_I64, MAX_PD_V_I64};
+
+  #[test]
+  fn test_defaults() {
+    let price = PriceBuilder::new().build();
+    assert_eq!(price.get_current_price(), Some(PriceConf { price: 0, conf: 0, expo: 0 }));
+    assert_eq!(price.get_publish_slot(), 1);
+    assert_eq!(price.num, 0);
+  }
+
+  #[cfg(target_endian = "little")]
+  #[test]
+  fn test_round_trip() {
+    let comp = |price: i64, status: PriceStatus| PriceComp {
+      agg: PriceInfo { price, conf: 1, status, ..Default::default() },
+      ..Default::default()
+    };
+    let builder = PriceBuilder::new()
+      .with_price(12345, 267)
+      .with_expo(-2)
+      .with_publish_slot(1000)
+      .with_components(&[comp(12340, PriceStatus::Trading), comp(12350, PriceStatus::Halted)]);
+
+    let data = builder.to_bytes();
+    let price = crate::load_price(&data).unwrap();
+    assert_eq!(*price, builder.build());
+    assert_eq!(price.get_current_price(), Some(PriceConf { price: 12345, conf: 267, expo: -2 }));
+    assert_eq!(price.get_price_no_older_than(1010, 10), price.get_current_price());
+    assert_eq!(price.num, 2);
+    assert_eq!(price.num_qt, 1);
+    assert_eq!(price.component_price_conf(0), Some(PriceConf { price: 12340, conf: 1, expo: -2 }));
+
+    let data = builder.with_status(PriceStatus::Halted).to_bytes();
+    assert_eq!(crate::load_price(&data).unwrap().get_current_price(), None);
+  }
+
+  #[test]
+  #[should_panic(expected = "too many components")]
+  fn test_too_many_components() {
+    PriceBuilder::new().with_components(&[PriceComp::default(); 33]);
+  }
+
+  #[test]
+  fn test_arbitrary_price_conf() {
+    assert_eq!(arbitrary_price_conf(42), arbitrary_price_conf(42));
+    let values: Vec<PriceConf> = (0..1000).map(arbitrary_price_conf).collect();
+    assert!(values.iter().any(|x| x.price == 0));
+    assert!(values.iter().any(|x| x.price > i64::MAX - 1000));
+    assert!(values.iter().any(|x| x.price < i64::MIN + 1000));
+    assert!(values.iter().any(|x| x.conf > u64::MAX - 1000));
+    assert!(values.iter().any(|x| x.expo > i32::MAX - 1000));
+    assert!(values.iter().any(|x| x.expo < i32::MIN + 1000));
+  }
+
+  #[test]
+  fn test_normalize_and_scale_properties() {
+    for seed in 0..20_000 {
+      let x = arbitrary_price_conf(seed);
+
+      // `normalize` never panics, and its result is in range.
+      if let Some(normalized) = x.normalize() {
+        assert!(normalized.price >= MIN_PD_V_I64 && normalized.price <= MAX_PD_V_I64, "{:?}", x);
+        assert!(normalized.conf <= MAX_PD_V_U64, "{:?}", x);
+        assert!(normalized.expo >= x.expo, "{:?}", x);
+      }
+
+      // Scaling to a smaller exponent is lossless, so scaling back gives the original value.
+      for shift in 0..4 {
+        let target = match x.expo.checked_sub(shift) {
+          Some(target) => target,
+          None => continue,
+        };
+        if let Some(scaled) = x.scale_to_exponent(target) {
+          assert_eq!(scaled.scale_to_exponent(x.expo), Some(x), "{:?} at {}", x, target);
+        }
+      }
+    }
+  }
+}
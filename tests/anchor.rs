@@ -0,0 +1,51 @@
+//! Checks that a price account can be loaded through Anchor's `Account` type.
+
+#![cfg(feature = "anchor")]
+
+use anchor_lang::{
+    prelude::{Account, AccountInfo, Pubkey},
+    AccountDeserialize, Owner,
+};
+use pyth_client::{AccountType, Price, PriceAccount, MAGIC, VERSION_2};
+
+fn price_data() -> Vec<u8> {
+    let mut data = vec![0u8; std::mem::size_of::<Price>()];
+    data[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&VERSION_2.to_le_bytes());
+    data[8..12].copy_from_slice(&(AccountType::Price as u32).to_le_bytes());
+    let size = data.len() as u32;
+    data[12..16].copy_from_slice(&size.to_le_bytes());
+    data[20..24].copy_from_slice(&(-8i32).to_le_bytes());
+    data
+}
+
+#[test]
+fn test_deserialize() {
+    let data = price_data();
+    let price = PriceAccount::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(price.expo, -8);
+
+    let mut wrong_magic = data.clone();
+    wrong_magic[0] = 0;
+    assert!(PriceAccount::try_deserialize(&mut &wrong_magic[..]).is_err());
+    let mut wrong_type = data;
+    wrong_type[8..12].copy_from_slice(&(AccountType::Product as u32).to_le_bytes());
+    assert!(PriceAccount::try_deserialize(&mut &wrong_type[..]).is_err());
+}
+
+#[test]
+fn test_account() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = price_data();
+    let owner = PriceAccount::owner();
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    let account: Account<PriceAccount> = Account::try_from(&info).unwrap();
+    assert_eq!(account.expo, -8);
+
+    let other = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = price_data();
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &other, false, 0);
+    assert!(Account::<PriceAccount>::try_from(&info).is_err());
+}
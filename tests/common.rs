@@ -3,11 +3,21 @@ use {
     pyth_client::processor::process_instruction,
     solana_program::instruction::Instruction,
     solana_program_test::*,
-    solana_sdk::{signature::Signer, transaction::Transaction, pubkey::Pubkey},
+    solana_sdk::{signature::Signer, transaction::{Transaction, TransactionError}, pubkey::Pubkey},
 };
 
 // Panics if running instruction fails
 pub async fn test_instr_exec_ok(instr: Instruction) {
+    test_instr_exec(instr).await.unwrap()
+}
+
+// Panics if running instruction succeeds, and returns the error otherwise
+#[allow(dead_code)]
+pub async fn test_instr_exec_err(instr: Instruction) -> TransactionError {
+    test_instr_exec(instr).await.unwrap_err().unwrap()
+}
+
+async fn test_instr_exec(instr: Instruction) -> Result<(), BanksClientError> {
     let (mut banks_client, payer, recent_blockhash) = ProgramTest::new(
         "pyth_client",
         id(),
@@ -20,5 +30,5 @@ pub async fn test_instr_exec_ok(instr: Instruction) {
         Some(&payer.pubkey()),
     );
     transaction.sign(&[&payer], recent_blockhash);
-    banks_client.process_transaction(transaction).await.unwrap()
+    banks_client.process_transaction(transaction).await
 }
@@ -1,6 +1,7 @@
 use {
-    pyth_client::{instruction, PriceConf},
+    pyth_client::{instruction, AccountType, Price, PriceConf, PriceStatus, MAGIC, VERSION_2},
     solana_program_test::*,
+    std::mem::size_of,
 };
 
 mod common;
@@ -34,6 +35,22 @@ async fn test_scale_to_exponent_best_case() {
     test_instr_exec_ok(instruction::scale_to_exponent(pc(1, u64::MAX, 10), 10)).await
 }
 
+#[tokio::test]
+async fn test_to_exponent_normalized_down() {
+    test_instr_exec_ok(instruction::to_exponent_normalized(pc(1, u64::MAX, -1000), 1000)).await
+}
+
+#[tokio::test]
+async fn test_to_exponent_normalized_up() {
+    test_instr_exec_ok(instruction::to_exponent_normalized(pc(1, 1, 0), -18)).await
+}
+
+#[tokio::test]
+async fn test_scale_to_exponent_up_same_result() {
+    // For comparison with test_to_exponent_normalized_up.
+    test_instr_exec_ok(instruction::scale_to_exponent(pc(1, 1, 0), -18)).await
+}
+
 #[tokio::test]
 async fn test_normalize_max_conf() {
     test_instr_exec_ok(instruction::normalize(pc(1, u64::MAX, 0))).await
@@ -85,3 +102,45 @@ async fn test_mul_max_price_2() {
         pc(i64::MAX, 1, 2),
     )).await;
 }
+
+#[tokio::test]
+async fn test_sub_max_price() {
+    test_instr_exec_ok(instruction::subtract(
+        pc(i64::MAX, u64::MAX / 2, 2),
+        pc(1, u64::MAX / 2, 2),
+    )).await;
+}
+
+#[tokio::test]
+async fn test_cmul_max_price() {
+    test_instr_exec_ok(instruction::cmul(pc(i64::MAX, u64::MAX, 0), i64::MAX, 0)).await;
+}
+
+#[tokio::test]
+async fn test_cmul_best_case() {
+    test_instr_exec_ok(instruction::cmul(pc(1, 1, 0), 1, 0)).await;
+}
+
+#[tokio::test]
+async fn test_weighted_mean_worst_case() {
+    // Most entries have to be scaled to the smallest exponent, 10^-18.
+    let prices = (0..32).map(|i| (pc(9, 1, -(i % 19)), u64::MAX / 64)).collect();
+    test_instr_exec_ok(instruction::weighted_mean(prices)).await;
+}
+
+#[tokio::test]
+async fn test_price_no_older_than() {
+    let mut price = Price {
+        magic: MAGIC,
+        ver: VERSION_2,
+        atype: AccountType::Price as u32,
+        size: size_of::<Price>() as u32,
+        ..Default::default()
+    };
+    price.agg.status = PriceStatus::Trading;
+    price.agg.pub_slot = 100;
+    price.valid_slot = 100;
+    // Measure the path that reads the price, not an early exit.
+    assert!(price.get_price_no_older_than(110, 25).is_some());
+    test_instr_exec_ok(instruction::price_no_older_than(&price, 110, 25)).await;
+}
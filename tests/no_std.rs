@@ -0,0 +1,17 @@
+//! Checks that the library can be used from a `#![no_std]` crate. CI also runs this with
+//! `--no-default-features`, so that the library itself is built without std.
+
+#![no_std]
+
+// The test harness itself requires std.
+extern crate std;
+
+use pyth_client::{load_price, PriceConf, PythError};
+
+#[test]
+fn test_no_std() {
+    let price = PriceConf { price: 12345, conf: 267, expo: -2 };
+    let quote = PriceConf { price: 200, conf: 0, expo: 0 };
+    assert!(price.div(&quote).is_some());
+    assert_eq!(load_price(&[0u8; 16]).map(|_| ()), Err(PythError::InvalidAccountData));
+}
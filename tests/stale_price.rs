@@ -1,81 +1,55 @@
 #![cfg(feature = "test-bpf")] // Only runs on bpf, where solana programs run
 
 use {
-    pyth_client::{MAGIC, VERSION_2, instruction, PriceType, Price, AccountType, AccKey, Ema, PriceComp, PriceInfo, CorpAction, PriceStatus},
+    pyth_client::{MAX_SLOT_DIFFERENCE, instruction, PriceStatus, test_utils::PriceBuilder},
     solana_program_test::*,
+    solana_sdk::{instruction::InstructionError, transaction::TransactionError},
 };
 
 
 mod common;
-use common::test_instr_exec_ok;
+use common::{test_instr_exec_err, test_instr_exec_ok};
 
-fn price_all_zero() -> Price {
-    let acc_key = AccKey {
-        val: [0; 32]
-    };
-
-    let ema = Ema {
-        val: 0,
-        numer: 0,
-        denom: 0
-    };
-
-    let price_info = PriceInfo {
-        conf: 0,
-        corp_act: CorpAction::NoCorpAct,
-        price: 0,
-        pub_slot: 0,
-        status: PriceStatus::Unknown
-    };
-
-    let price_comp = PriceComp {
-        agg: price_info,
-        latest: price_info,
-        publisher: acc_key
-    };
-
-    Price {
-        magic: MAGIC,
-        ver: VERSION_2,
-        atype: AccountType::Price as u32,
-        size: 0,
-        ptype: PriceType::Price,
-        expo: 0,
-        num: 0,
-        num_qt: 0,
-        last_slot: 0,
-        valid_slot: 0,
-        ema_price: ema,
-        ema_confidence: ema,
-        drv1: 0,
-        drv2: 0,
-        prod: acc_key,
-        next: acc_key,
-        prev_slot: 0,
-        prev_price: 0,
-        prev_conf: 0,
-        drv3: 0,
-        agg: price_info,
-        comp: [price_comp; 32]
-    }
+#[tokio::test]
+async fn test_price_not_stale() {
+    let price = PriceBuilder::new().with_publish_slot(0).build();
+    test_instr_exec_ok(instruction::price_status_check(&price, 0, PriceStatus::Trading)).await;
 }
 
 
 #[tokio::test]
-async fn test_price_not_stale() {
-    let mut price = price_all_zero();
-    price.agg.status = PriceStatus::Trading;
-    test_instr_exec_ok(instruction::price_status_check(&price, PriceStatus::Trading)).await;
+async fn test_price_stale() {
+    let price = PriceBuilder::new().with_publish_slot(100).build();
+    test_instr_exec_ok(instruction::price_status_check(&price, 100 + MAX_SLOT_DIFFERENCE, PriceStatus::Trading)).await;
+    test_instr_exec_ok(instruction::price_status_check(&price, 101 + MAX_SLOT_DIFFERENCE, PriceStatus::Unknown)).await;
+    // A publish slot after the current slot is not stale.
+    test_instr_exec_ok(instruction::price_status_check(&price, 5, PriceStatus::Trading)).await;
 }
 
+#[tokio::test]
+async fn test_batch_status_check() {
+    let price = PriceBuilder::new().with_publish_slot(0).build();
+    let halted = PriceBuilder::new().with_publish_slot(0).with_status(PriceStatus::Halted).build();
+
+    test_instr_exec_ok(instruction::batch_status_check(&[price, price, price], 0, PriceStatus::Trading)).await;
+    // Statuses are evaluated at the given slot, so stale prices report Unknown.
+    test_instr_exec_ok(instruction::batch_status_check(&[price, price], 1 + MAX_SLOT_DIFFERENCE, PriceStatus::Unknown)).await;
+    // The error encodes the index of the first account that does not have the expected status.
+    assert_eq!(
+        test_instr_exec_err(instruction::batch_status_check(&[price, price, halted, halted], 0, PriceStatus::Trading)).await,
+        TransactionError::InstructionError(0, InstructionError::Custom(2))
+    );
+}
 
 #[tokio::test]
-async fn test_price_stale() {
-    let mut price = price_all_zero();
-    price.agg.status = PriceStatus::Trading;
-    // Value 100 will cause an overflow because this is bigger than Solana slot in the test suite (its ~1-5).
-    // As the check will be 5u - 100u ~= 1e18 > MAX_SLOT_DIFFERENCE. It can only break when Solana slot in the test suite becomes 
-    // between 100 and 100+MAX_SLOT_DIFFERENCE.
-    price.agg.pub_slot = 100;
-    test_instr_exec_ok(instruction::price_status_check(&price, PriceStatus::Unknown)).await;
+async fn test_batch_status_check_invalid_account() {
+    let price = PriceBuilder::new().build();
+    let mut invalid = price;
+    invalid.magic = 0;
+
+    // An account that fails to load is reported by its index, like a status mismatch.
+    assert_eq!(
+        test_instr_exec_err(instruction::batch_status_check(&[price, invalid, price], 0, PriceStatus::Trading)).await,
+        TransactionError::InstructionError(0, InstructionError::Custom(1))
+    );
 }